@@ -0,0 +1,93 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use fchess::{
+    chess_data::fen_parser,
+    engine::BENCH_POSITIONS,
+    sliders::{bishop_moves, rook_moves},
+    structs::{Board, ChessTables},
+};
+
+fn positions() -> Vec<Board> {
+    BENCH_POSITIONS.iter().map(|fen| fen_parser(fen)).collect()
+}
+
+fn legal_moves(c: &mut Criterion) {
+    let tables = ChessTables::default();
+    let boards = positions();
+
+    let mut group = c.benchmark_group("legal_moves");
+    group.throughput(Throughput::Elements(boards.len() as u64));
+    group.bench_function("get_all_legal_moves", |b| {
+        b.iter(|| {
+            for board in &boards {
+                black_box(board.get_all_legal_moves(&tables));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn make_move(c: &mut Criterion) {
+    let tables = ChessTables::default();
+    let moves: Vec<(Board, u16)> = positions()
+        .into_iter()
+        .flat_map(|board| {
+            let legal_moves = board.get_all_legal_moves(&tables);
+            legal_moves.move_buffer[..legal_moves.length as usize]
+                .iter()
+                .map(|chess_move| (board.clone(), *chess_move))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("make_move");
+    group.throughput(Throughput::Elements(moves.len() as u64));
+    group.bench_function("move_piece", |b| {
+        b.iter(|| {
+            for (board, chess_move) in &moves {
+                black_box(board.move_piece(*chess_move));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn sliders(c: &mut Criterion) {
+    let tables = ChessTables::default();
+    let occupancies: Vec<_> = positions()
+        .iter()
+        .map(|board| {
+            board
+                .bitboards
+                .iter()
+                .flatten()
+                .fold(fchess::bitboard::BitBoard(0), |occupancy, bitboard| {
+                    occupancy | *bitboard
+                })
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("sliders");
+    group.throughput(Throughput::Elements(64 * occupancies.len() as u64));
+    group.bench_function("bishop_moves", |b| {
+        b.iter(|| {
+            for occupancy in &occupancies {
+                for position in 0..64 {
+                    black_box(bishop_moves(position, *occupancy, &tables));
+                }
+            }
+        })
+    });
+    group.bench_function("rook_moves", |b| {
+        b.iter(|| {
+            for occupancy in &occupancies {
+                for position in 0..64 {
+                    black_box(rook_moves(position, *occupancy, &tables));
+                }
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, legal_moves, make_move, sliders);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use fchess::{
+    chess_data::fen_parser, engine::BENCH_POSITIONS, move_generation::perft, structs::ChessTables,
+};
+
+const PERFT_DEPTH: u8 = 3;
+
+fn perft_nodes(c: &mut Criterion) {
+    let tables = ChessTables::default();
+
+    let mut group = c.benchmark_group("perft");
+    group.sample_size(10);
+    for (index, fen) in BENCH_POSITIONS.iter().enumerate() {
+        let board = fen_parser(fen);
+        // Reporting throughput in nodes makes criterion print nodes per second.
+        let nodes = perft(board.clone(), PERFT_DEPTH, &tables).nodes;
+        group.throughput(Throughput::Elements(nodes as u64));
+        group.bench_function(format!("position_{}", index), |b| {
+            b.iter(|| perft(board.clone(), PERFT_DEPTH, &tables))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, perft_nodes);
+criterion_main!(benches);
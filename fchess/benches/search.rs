@@ -0,0 +1,25 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use fchess::{
+    chess_data::fen_parser,
+    engine::{bench_position, BENCH_POSITIONS},
+    structs::ChessTables,
+};
+
+const SEARCH_DEPTH: usize = 3;
+
+fn fixed_depth_search(c: &mut Criterion) {
+    let tables = ChessTables::default();
+
+    let mut group = c.benchmark_group("search");
+    group.sample_size(10);
+    for (index, fen) in BENCH_POSITIONS.iter().enumerate() {
+        let board = fen_parser(fen);
+        group.bench_function(format!("position_{}", index), |b| {
+            b.iter(|| bench_position(SEARCH_DEPTH, &board, &tables))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, fixed_depth_search);
+criterion_main!(benches);
@@ -234,24 +234,14 @@ fn generate_bishop_moves() -> [BitBoard; 64] {
 }
 
 fn generate_bishop_moves_short() -> [BitBoard; 64] {
-    let mut rook_moves = generate_bishop_moves();
+    let mut bishop_moves = generate_bishop_moves();
 
+    // Unlike rooks, a bishop can never slide along an edge, so the whole border is irrelevant to blockers.
     for position in 0..64 {
-        if (rook_moves[position] & TOP).0.count_ones() == 1 {
-            rook_moves[position] &= !TOP;
-        }
-        if (rook_moves[position] & LEFT).0.count_ones() == 1 {
-            rook_moves[position] &= !LEFT;
-        }
-        if (rook_moves[position] & RIGHT).0.count_ones() == 1 {
-            rook_moves[position] &= !RIGHT;
-        }
-        if (rook_moves[position] & BOTTOM).0.count_ones() == 1 {
-            rook_moves[position] &= !BOTTOM;
-        }
+        bishop_moves[position] &= !(TOP | LEFT | RIGHT | BOTTOM);
     }
 
-    rook_moves
+    bishop_moves
 }
 
 fn generate_queen_moves() -> [BitBoard; 64] {
@@ -396,6 +386,80 @@ pub fn fen_parser(fen: &str) -> Board {
     };
 
     board.turn = turn;
+    board.hash = board.compute_hash();
+    board.pawn_hash = board.compute_pawn_hash();
+    board.piece_square = board.compute_piece_square();
+    board.phase = board.compute_phase();
 
     board
 }
+
+pub fn fen_writer(board: &Board) -> String {
+    // The board doesn't track move counters, so those are always written as "0 1".
+    let piece_characters = [
+        ['K', 'Q', 'R', 'B', 'N', 'P'],
+        ['k', 'q', 'r', 'b', 'n', 'p'],
+    ];
+
+    let mut placement = String::new();
+    for rank in (0..8).rev() {
+        let mut empty_squares = 0;
+        for file in (0..8).rev() {
+            let position = rank * 8 + file; // H1 is 0, so files go from A to H by counting down.
+            let mut character = None;
+            for color in 0..2 {
+                for piece in 0..6 {
+                    if board.bitboards[color][piece].get_bit(position) {
+                        character = Some(piece_characters[color][piece]);
+                    }
+                }
+            }
+
+            match character {
+                Some(character) => {
+                    if empty_squares != 0 {
+                        placement.push_str(&empty_squares.to_string());
+                        empty_squares = 0;
+                    }
+                    placement.push(character);
+                }
+                None => empty_squares += 1,
+            }
+        }
+        if empty_squares != 0 {
+            placement.push_str(&empty_squares.to_string());
+        }
+        if rank != 0 {
+            placement.push('/');
+        }
+    }
+
+    let turn = match board.turn {
+        Color::White => "w",
+        Color::Black => "b",
+    };
+
+    let mut castling = String::new();
+    if board.castling_rights.white_kingside {
+        castling.push('K');
+    }
+    if board.castling_rights.white_queenside {
+        castling.push('Q');
+    }
+    if board.castling_rights.black_kingside {
+        castling.push('k');
+    }
+    if board.castling_rights.black_queenside {
+        castling.push('q');
+    }
+    if castling.is_empty() {
+        castling.push('-');
+    }
+
+    let en_passant = match board.en_passant {
+        Some(position) => HUMAN_READBLE_SQAURES[position as usize].to_lowercase(),
+        None => "-".to_string(),
+    };
+
+    format!("{} {} {} {} 0 1", placement, turn, castling, en_passant)
+}
@@ -6,7 +6,6 @@ pub const ROOK_VALUE: i32 = 500;
 pub const BISHOP_VALUE: i32 = 350;
 pub const KNIGHT_VALUE: i32 = 300;
 pub const PAWN_VALUE: i32 = 100;
-pub const MOBILITY_VALUE: i32 = 1; // PLACEHOLDER
 
 pub const MAX_LEGAL_MOVES: usize = 32;
 
@@ -44,7 +43,6 @@ pub const BLACK_QUEENSIDE_HITMASK_BLOCKERS: BitBoard = BitBoard(0x70000000000000
 
 pub const EMPTY_STRING: String = String::new();
 
-#[cfg(test)]
 pub const STARTING_POSITION_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
 pub const HUMAN_READBLE_SQAURES: [&str; 64] = [
@@ -1,166 +1,965 @@
-use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::thread;
-use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
-use crate::bitboard::BitBoard;
+use crate::chess_data::fen_parser;
 use crate::constants::*;
-use crate::move_generation::human_readable_position;
+use crate::evaluation::evaluate;
+use crate::move_picker::{MoveOrdering, MovePicker, MAX_PLY};
+use crate::pawns::PawnTable;
+use crate::repetition::RepetitionStack;
+use crate::score::Score;
+use crate::time_manager::{TimeControl, TimeManager};
+use crate::transposition::{Bound, TranspositionEntry, TranspositionTable};
 use crate::Board;
 use crate::BoardState;
 use crate::ChessMove;
 use crate::ChessTables;
-use crate::Color;
-use crate::Pieces;
 
-static LARGE_VALUE_SAFE: i32 = 999_999; // Number that is large enough to overshadow any other number, but not so large it will overflow.
+const TIME_CHECK_INTERVAL: u64 = 2048; // Nodes between clock checks.
+const ASPIRATION_MIN_DEPTH: usize = 4; // Shallower scores jump around too much.
+const ASPIRATION_WINDOW: i32 = 25; // Centipawns either side of the last score, doubled after every miss.
+const MAX_ASPIRATION_WINDOW: i32 = 1000; // Past this, give up and open the window fully.
 
+// Triangular PV table, https://www.chessprogramming.org/Triangular_PV-Table
+// Row `ply` holds the best line from that ply on, built from the row below whenever alpha is raised.
+struct PrincipalVariation {
+    moves: [[u16; MAX_PLY]; MAX_PLY],
+    length: [usize; MAX_PLY],
+}
+
+impl PrincipalVariation {
+    fn new() -> Box<PrincipalVariation> {
+        Box::new(PrincipalVariation {
+            moves: [[0; MAX_PLY]; MAX_PLY],
+            length: [0; MAX_PLY],
+        })
+    }
+
+    fn clear(&mut self, ply: usize) {
+        self.length[ply] = 0;
+    }
+
+    fn update(&mut self, ply: usize, chess_move: u16) {
+        let child_length = match ply + 1 < MAX_PLY {
+            true => self.length[ply + 1].min(MAX_PLY - ply - 1),
+            false => 0,
+        };
+        let (row, rows_below) = self.moves.split_at_mut(ply + 1);
+        row[ply][0] = chess_move;
+        if child_length > 0 {
+            row[ply][1..=child_length].copy_from_slice(&rows_below[0][..child_length]);
+        }
+        self.length[ply] = child_length + 1;
+    }
+
+    fn line(&self, ply: usize) -> &[u16] {
+        &self.moves[ply][..self.length[ply]]
+    }
+}
+
+// Shared by every search thread.
+struct SearchContext<'a> {
+    tables: &'a ChessTables,
+    transposition_table: &'a TranspositionTable,
+    time: TimeManager,
+    node_limit: Option<u64>,
+    nodes: AtomicU64,     // Over every thread and iteration.
+    stop: &'a AtomicBool, // Set from outside the search, e.g. by the UCI "stop" command.
+    finished: AtomicBool, // Set by the main thread once it's done, stops the helper threads.
+    parameters: SearchParameters,
+}
+
+// Everything a search thread needs besides the position itself.
+struct SearchThread<'a> {
+    context: &'a SearchContext<'a>,
+    interruptible: bool, // Whether the limits can stop the search, only for the main thread after its first iteration.
+    ordering: Box<MoveOrdering>, // Each thread learns its own, only the transposition table is shared.
+    pawn_table: PawnTable,
+    move_stack: Vec<u16>, // Moves played from the root to the current node.
+    pv: Box<PrincipalVariation>,
+    nodes: u64,
+    seldepth: usize,                // The furthest ply reached, including quiescence.
+    stopped: bool, // Once set every node returns straight away, the result of the iteration is thrown out.
+    root_depth: i32, // Of the current iteration, extensions on a line can add at most this much again.
+    excluded_moves: [u16; MAX_PLY], // By ply, skipped while checking whether the hash move is singular.
+    repetitions: RepetitionStack, // The game's positions and the ones from the root to the current node.
+}
+
+impl<'a> SearchThread<'a> {
+    fn new(context: &'a SearchContext<'a>, repetitions: RepetitionStack) -> SearchThread<'a> {
+        SearchThread {
+            context,
+            interruptible: false,
+            ordering: Box::default(),
+            pawn_table: PawnTable::default(),
+            move_stack: Vec::with_capacity(MAX_PLY),
+            pv: PrincipalVariation::new(),
+            nodes: 0,
+            seldepth: 0,
+            stopped: false,
+            root_depth: 0,
+            excluded_moves: [0; MAX_PLY],
+            repetitions,
+        }
+    }
+
+    // The extension budget, a line can be extended by at most the depth of the iteration.
+    // Each extension keeps ply + depth from shrinking, so the sum tells how much has been used.
+    fn can_extend(&self, ply: usize, depth: i32) -> bool {
+        ply as i32 + depth < 2 * self.root_depth
+    }
+
+    // Counts the node and checks the limits, returns true if the search has to stop.
+    // The clock is only checked every so often, reading the time every node is too slow.
+    fn visit_node(&mut self, ply: usize) -> bool {
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply + 1);
+        let total_nodes = self.context.nodes.fetch_add(1, Ordering::Relaxed) + 1;
+        let out_of_limits = self.interruptible
+            && (self
+                .context
+                .node_limit
+                .is_some_and(|limit| total_nodes >= limit)
+                || (self.nodes.is_multiple_of(TIME_CHECK_INTERVAL)
+                    && self.context.time.out_of_time()));
+        if out_of_limits
+            || self.context.stop.load(Ordering::Relaxed)
+            || self.context.finished.load(Ordering::Relaxed)
+        {
+            self.stopped = true;
+        }
+        self.stopped
+    }
+}
+
+const MAX_QUIESCENCE_PLY: usize = MAX_PLY - 1; // Captures run out long before this, it's only a safety net.
+
+// Quiescence search, https://www.chessprogramming.org/Quiescence_Search
+// Only evaluates quiet positions by playing out captures and promotions first, so an exchange isn't cut off halfway.
+// In check every evasion is searched, since standing pat isn't an option.
+fn quiescence(
+    ply: usize,
+    board: Board,
+    mut alpha: Score,
+    beta: Score,
+    search: &mut SearchThread,
+) -> Score {
+    search.pv.clear(ply);
+    if search.visit_node(ply) {
+        return Score::DRAW;
+    }
+
+    let in_check = board.is_in_check(search.context.tables);
+    let move_data = board.get_all_legal_moves(search.context.tables);
+    if move_data.length == 0 {
+        return match in_check {
+            true => Score::mated(ply),
+            false => Score::DRAW, // Stalemate
+        };
+    }
+    if ply >= MAX_QUIESCENCE_PLY {
+        return Score::centipawns(evaluate(
+            &board,
+            search.context.tables,
+            &mut search.pawn_table,
+        ));
+    }
+
+    let mut max_score = Score::mated(ply);
+    if !in_check {
+        // Stand pat, assume there is a quiet move at least as good as doing nothing.
+        let stand_pat = Score::centipawns(evaluate(
+            &board,
+            search.context.tables,
+            &mut search.pawn_table,
+        ));
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+        max_score = stand_pat;
+    }
+
+    let mut moves: Vec<(i32, u16)> = move_data.move_buffer[..move_data.length as usize]
+        .iter()
+        .filter(|&&chess_move| in_check || board.is_noisy(chess_move))
+        .map(|&chess_move| (board.mvv_lva(chess_move), chess_move))
+        .collect();
+    moves.sort_unstable_by(|a, b| b.cmp(a));
+
+    for (_, chess_move) in moves {
+        let score = -quiescence(ply + 1, board.move_piece(chess_move), -beta, -alpha, search);
+        if search.stopped {
+            return Score::DRAW;
+        }
+        max_score = max_score.max(score);
+
+        if score >= beta {
+            break;
+        }
+        if score > alpha {
+            alpha = score;
+            search.pv.update(ply, chess_move);
+        }
+    }
+
+    max_score
+}
+
+// Pruning margins and reductions, in centipawns and plies.
+const REVERSE_FUTILITY_MAX_DEPTH: i32 = 6;
+const REVERSE_FUTILITY_MARGIN: i32 = 80; // Per ply of depth.
+const NULL_MOVE_MIN_DEPTH: i32 = 3;
+const NULL_MOVE_REDUCTION: i32 = 3; // Plus one for every 6 plies of depth.
+const FUTILITY_MAX_DEPTH: i32 = 3;
+const FUTILITY_MARGIN: i32 = 120; // Per ply of depth.
+const LATE_MOVE_MIN_DEPTH: i32 = 3;
+const LATE_MOVE_MIN_MOVES: usize = 3; // Moves searched before any are reduced.
+const SINGULAR_MIN_DEPTH: i32 = 6;
+const SINGULAR_MARGIN: i32 = 3; // Per ply of depth, how much worse every other move has to be.
+
+// Late moves are searched less deeply the later they come and the deeper the search, https://www.chessprogramming.org/Late_Move_Reductions
+fn late_move_reduction(depth: i32, moves_searched: usize) -> i32 {
+    (0.75 + (depth as f64).ln() * (moves_searched as f64).ln() / 2.25) as i32
+}
+
+// Principal variation search, https://www.chessprogramming.org/Principal_Variation_Search
+// `ply` counts from the root, `depth` is what's left until quiescence takes over.
+// Nodes with a zero window (beta == alpha + 1) only have to prove a move is better or worse, so they are pruned and reduced more.
 fn negamax(
-    depth: usize,
-    max_depth: usize,
+    ply: usize,
+    depth: i32,
     board: Board,
-    mut move_history: HashMap<[[BitBoard; 6]; 2], u8>,
-    mut alpha: i32,
-    beta: i32,
-    tables: &ChessTables,
-) -> i32 {
+    mut alpha: Score,
+    mut beta: Score,
+    search: &mut SearchThread,
+) -> Score {
+    search.pv.clear(ply);
+    if depth <= 0 || ply >= MAX_PLY - 1 {
+        return quiescence(ply, board, alpha, beta, search);
+    }
+
+    if search.visit_node(ply) {
+        return Score::DRAW;
+    }
+    let tables = search.context.tables;
     match board.get_board_state(tables) {
-        BoardState::Checkmate => return -LARGE_VALUE_SAFE + (depth as i32), // Score checkmates at a higher depth lower, meaning the engine will choose the fastest checkmate (or slowest if negative score).
-        BoardState::Stalemate => return 0,                                  // Equal position
+        BoardState::Checkmate => return Score::mated(ply), // Mates further from the root score lower, so the engine picks the fastest one (or slowest if it's the one getting mated).
+        BoardState::Stalemate => return Score::DRAW,
         BoardState::OnGoing => {}
     }
-    if let Some(value) = move_history.get(&board.bitboards) {
-        if *value == 2 {
-            // We've seen it twice in the history, I'm also seeing it now, so it's three.
-            return 0; // Threefold
+    if search.repetitions.is_draw() {
+        return Score::DRAW;
+    }
+
+    // Mate distance pruning, https://www.chessprogramming.org/Mate_Distance_Pruning
+    // Even mating right away can't beat a shorter mate already found elsewhere in the tree.
+    alpha = alpha.max(Score::mated(ply));
+    beta = beta.min(Score::mate(ply + 1));
+    if alpha >= beta {
+        return alpha;
+    }
+
+    let pv_node = beta > alpha + 1;
+    let original_alpha = alpha;
+    let excluded_move = search.excluded_moves[ply];
+    let mut hash_move = 0;
+    let mut hash_entry = None;
+    if let Some(entry) = search
+        .context
+        .transposition_table
+        .probe(board.hash, ply)
+        .filter(|_| excluded_move == 0)
+    // The entry is for the whole position, not the one without the excluded move.
+    {
+        hash_move = entry.best_move;
+        hash_entry = Some(entry);
+        // Not in PV nodes, a cutoff there would cut the principal variation short.
+        if entry.depth as i32 >= depth && !pv_node {
+            match entry.bound {
+                Bound::Exact => return entry.score,
+                Bound::Lower if entry.score >= beta => return entry.score,
+                Bound::Upper if entry.score <= alpha => return entry.score,
+                _ => {}
+            }
         }
     }
-    if depth == max_depth {
-        return evaluate(&board, tables);
+
+    let parameters = search.context.parameters;
+    let in_check = board.is_in_check(tables);
+    let previous_move = search.move_stack.last().copied().unwrap_or(0);
+    let mut futility_pruning = false;
+    if !pv_node && !in_check && !beta.is_mate() && excluded_move == 0 {
+        let static_eval = Score::centipawns(evaluate(&board, tables, &mut search.pawn_table));
+
+        // Reverse futility pruning, so far above beta that the opponent can't catch up in the plies left.
+        if parameters.reverse_futility_pruning
+            && depth <= REVERSE_FUTILITY_MAX_DEPTH
+            && static_eval - REVERSE_FUTILITY_MARGIN * depth >= beta
+        {
+            return static_eval;
+        }
+
+        // Null-move pruning, https://www.chessprogramming.org/Null_Move_Pruning
+        // If passing still beats beta, a real move will too. Never twice in a row, and not when zugzwang is likely.
+        if parameters.null_move_pruning
+            && depth >= NULL_MOVE_MIN_DEPTH
+            && static_eval >= beta
+            && previous_move != 0
+            && board.has_non_pawn_material(board.turn)
+        {
+            let reduction = NULL_MOVE_REDUCTION + depth / 6;
+            let null_board = board.make_null_move();
+            search.move_stack.push(0);
+            search.repetitions.push(null_board.hash, true);
+            let score = -negamax(
+                ply + 1,
+                depth - 1 - reduction,
+                null_board,
+                -beta,
+                -beta + 1,
+                search,
+            );
+            search.repetitions.pop();
+            search.move_stack.pop();
+            if search.stopped {
+                return Score::DRAW;
+            }
+            if score >= beta {
+                return score.min(Score::MAX_EVALUATION); // A mate found after passing isn't proven.
+            }
+        }
+
+        // Futility pruning, quiet moves can't raise alpha when even a generous margin doesn't reach it.
+        futility_pruning = parameters.futility_pruning
+            && depth <= FUTILITY_MAX_DEPTH
+            && static_eval + FUTILITY_MARGIN * depth <= alpha;
     }
 
-    let mut move_data = board.get_all_legal_moves(tables);
-    move_data.move_buffer.sort_unstable();
-    move_data.move_buffer.reverse();
+    // Singular extensions, https://www.chessprogramming.org/Singular_Extensions
+    // If every move but the hash move fails low by a margin, the hash move is the only good one and gets extended.
+    let mut singular_move = 0;
+    if let Some(entry) = hash_entry.filter(|entry| {
+        parameters.singular_extensions
+            && depth >= SINGULAR_MIN_DEPTH
+            && entry.best_move != 0
+            && entry.bound != Bound::Upper
+            && entry.depth as i32 >= depth - 3
+            && !entry.score.is_mate()
+            && search.can_extend(ply, depth)
+    }) {
+        let singular_beta = entry.score - SINGULAR_MARGIN * depth;
+        search.excluded_moves[ply] = entry.best_move;
+        let score = negamax(
+            ply,
+            (depth - 1) / 2,
+            board.clone(),
+            singular_beta - 1,
+            singular_beta,
+            search,
+        );
+        search.excluded_moves[ply] = 0;
+        if search.stopped {
+            return Score::DRAW;
+        }
+        if score < singular_beta {
+            singular_move = entry.best_move;
+        }
+    }
+
+    let move_data = board.get_all_legal_moves(tables);
+    let move_picker = MovePicker::new(
+        &board,
+        &move_data,
+        hash_move,
+        ply,
+        previous_move,
+        &search.ordering,
+    );
 
-    let mut max_score = i32::MIN;
-    for possible_move in 0..move_data.length {
-        let legal_move = move_data.move_buffer[possible_move as usize];
+    let mut max_score = -Score::INFINITE;
+    let mut best_move = 0;
+    let mut quiets_tried = Vec::new();
+    let mut moves_searched = 0;
+    for legal_move in move_picker {
+        if legal_move == excluded_move {
+            continue;
+        }
+        let is_quiet = !board.is_noisy(legal_move);
         let new_board = board.move_piece(legal_move);
-        let possible_seen_count = move_history.get(&new_board.bitboards);
-        match possible_seen_count {
-            Some(value) => move_history.insert(board.bitboards, value + 1),
-            None => move_history.insert(board.bitboards, 1),
+        let gives_check = new_board.is_in_check(tables);
+        if futility_pruning && moves_searched > 0 && is_quiet && !gives_check {
+            continue;
+        }
+
+        // Forcing lines shouldn't stop at the same horizon as quiet ones, at most one extra ply per move.
+        let extension = match search.can_extend(ply, depth)
+            && ((parameters.check_extensions && gives_check)
+                || (parameters.one_reply_extensions && move_data.length == 1)
+                || legal_move == singular_move)
+        {
+            true => 1,
+            false => 0,
         };
-        let score = -negamax(
-            depth + 1,
-            max_depth,
-            new_board,
-            move_history.clone(),
-            -beta, // Flip these values as maximizing player changes.
-            -alpha,
-            tables,
-        );
-        max_score = std::cmp::max(max_score, score);
+        let child_depth = depth - 1 + extension;
 
-        if score >= beta {
-            break;
+        search.move_stack.push(legal_move);
+        search
+            .repetitions
+            .push(new_board.hash, board.is_irreversible(legal_move));
+        let score = if moves_searched == 0 {
+            -negamax(
+                ply + 1,
+                child_depth,
+                new_board,
+                -beta, // Flip these values as maximizing player changes.
+                -alpha,
+                search,
+            )
+        } else {
+            // Later moves are expected to fail low, so first check that cheaply with a zero window and a reduction.
+            let reduction = match parameters.late_move_reductions
+                && depth >= LATE_MOVE_MIN_DEPTH
+                && moves_searched >= LATE_MOVE_MIN_MOVES
+                && is_quiet
+                && !in_check
+                && !gives_check
+            {
+                true => (late_move_reduction(depth, moves_searched) - pv_node as i32)
+                    .clamp(0, depth - 2),
+                false => 0,
+            };
+            let window_beta = match parameters.principal_variation_search {
+                true => alpha + 1,
+                false => beta,
+            };
+
+            let mut score = -negamax(
+                ply + 1,
+                child_depth - reduction,
+                new_board.clone(),
+                -window_beta,
+                -alpha,
+                search,
+            );
+            if reduction > 0 && score > alpha {
+                score = -negamax(
+                    ply + 1,
+                    child_depth,
+                    new_board.clone(),
+                    -window_beta,
+                    -alpha,
+                    search,
+                );
+            }
+            if window_beta != beta && score > alpha && score < beta {
+                score = -negamax(ply + 1, child_depth, new_board, -beta, -alpha, search);
+            }
+            score
+        };
+        search.repetitions.pop();
+        search.move_stack.pop();
+        if search.stopped {
+            return Score::DRAW; // Don't store anything, the score is incomplete.
         }
+        moves_searched += 1;
+        if score > max_score {
+            max_score = score;
+            best_move = legal_move;
+        }
+
         if score > alpha {
             alpha = score;
+            search.pv.update(ply, legal_move); // Even on a cutoff, mate distance pruning can make beta the exact score.
+        }
+        if score >= beta {
+            if is_quiet {
+                search.ordering.update_quiet_cutoff(
+                    &board,
+                    ply,
+                    depth as u8,
+                    legal_move,
+                    previous_move,
+                    &quiets_tried,
+                );
+            }
+            break;
         }
+        if is_quiet {
+            quiets_tried.push(legal_move);
+        }
+    }
+
+    let bound = if max_score >= beta {
+        Bound::Lower
+    } else if max_score <= original_alpha {
+        best_move = 0; // Every move failed low, so none of them is known to be best.
+        Bound::Upper
+    } else {
+        Bound::Exact
+    };
+    if excluded_move == 0 {
+        search.context.transposition_table.store(
+            board.hash,
+            ply,
+            TranspositionEntry {
+                depth: depth as u8,
+                bound,
+                score: max_score,
+                best_move,
+            },
+        );
     }
 
     max_score
 }
 
-pub fn get_best_move(
+struct RootSearch {
+    score: Score,    // Exact if inside the window, otherwise a bound like in negamax.
+    pv: Vec<u16>,    // Starting with the best move, empty if no move raised alpha.
+    completed: bool, // False if the search was stopped, in which case the best move may not be.
+}
+
+// Scores the root moves inside the window, the best score so far is used as alpha so later moves only have to prove they are worse.
+// The best move is moved to the front, so the next iteration searches it first.
+// The depth is what's left after the root move is played, so a depth N search, like "go depth N", passes N - 1.
+fn search_root(
     depth: usize,
-    board: Board,
-    move_history: HashMap<[[BitBoard; 6]; 2], u8>,
+    board: &Board,
+    root_moves: &mut [u16],
+    (mut alpha, beta): (Score, Score),
+    search: &mut SearchThread,
+) -> RootSearch {
+    let mut root_search = RootSearch {
+        score: -Score::INFINITE,
+        pv: Vec::new(),
+        completed: true,
+    };
+    let mut best_index = 0;
+    search.root_depth = depth as i32;
+
+    for (index, &chess_move) in root_moves.iter().enumerate() {
+        let new_board = board.move_piece(chess_move);
+        search.move_stack.push(chess_move);
+        search
+            .repetitions
+            .push(new_board.hash, board.is_irreversible(chess_move));
+        let mut score = Score::INFINITE;
+        if index > 0 && search.context.parameters.principal_variation_search {
+            score = -negamax(
+                0,
+                depth as i32,
+                new_board.clone(),
+                -alpha - 1,
+                -alpha,
+                search,
+            );
+        }
+        if score > alpha {
+            score = -negamax(
+                0,
+                depth as i32,
+                new_board,
+                -beta, // Flipped, as in negamax.
+                -alpha,
+                search,
+            );
+        }
+        search.repetitions.pop();
+        search.move_stack.pop();
+        if search.stopped {
+            root_search.completed = false;
+            break;
+        }
+        root_search.score = root_search.score.max(score);
+        if score > alpha {
+            alpha = score;
+            root_search.pv = vec![chess_move];
+            root_search.pv.extend_from_slice(search.pv.line(0));
+            best_index = index;
+        }
+        if score >= beta {
+            break;
+        }
+    }
+
+    root_moves[..=best_index].rotate_right(1);
+    root_search
+}
+
+// What the search is allowed to do, anything left as None is unlimited.
+#[derive(Clone, Debug)]
+pub struct SearchLimits {
+    pub depth: Option<usize>, // In plies, counting the root move.
+    pub nodes: Option<u64>,
+    pub mate: Option<u32>, // Stop once a mate in this many moves or fewer is found.
+    pub time_control: TimeControl, // movetime and the clock.
+    pub infinite: bool,    // Ignore the clock, only a depth or node limit stops the search.
+    pub searchmoves: Vec<u16>, // Only consider these root moves, all of them if empty.
+    pub threads: usize,
+    pub parameters: SearchParameters,
+}
+
+// Switches for the selective parts of the search, the pruning, reductions and extensions. Everything is on by default.
+// Turning them off is for testing, e.g. to check a pruning doesn't change the result of a position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchParameters {
+    pub principal_variation_search: bool,
+    pub null_move_pruning: bool,
+    pub late_move_reductions: bool,
+    pub reverse_futility_pruning: bool,
+    pub futility_pruning: bool,
+    pub check_extensions: bool,
+    pub one_reply_extensions: bool,
+    pub singular_extensions: bool,
+}
+
+impl Default for SearchParameters {
+    fn default() -> SearchParameters {
+        SearchParameters {
+            principal_variation_search: true,
+            null_move_pruning: true,
+            late_move_reductions: true,
+            reverse_futility_pruning: true,
+            futility_pruning: true,
+            check_extensions: true,
+            one_reply_extensions: true,
+            singular_extensions: true,
+        }
+    }
+}
+
+impl SearchParameters {
+    // Plain alpha-beta, to a fixed depth.
+    pub fn none() -> SearchParameters {
+        SearchParameters {
+            principal_variation_search: false,
+            null_move_pruning: false,
+            late_move_reductions: false,
+            reverse_futility_pruning: false,
+            futility_pruning: false,
+            check_extensions: false,
+            one_reply_extensions: false,
+            singular_extensions: false,
+        }
+    }
+}
+
+impl Default for SearchLimits {
+    fn default() -> SearchLimits {
+        SearchLimits {
+            depth: None,
+            nodes: None,
+            mate: None,
+            time_control: TimeControl::default(),
+            infinite: false,
+            searchmoves: Vec::new(),
+            threads: 1,
+            parameters: SearchParameters::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchScore {
+    Centipawns(i32),
+    Mate(i32), // In moves, negative when the side to move is getting mated.
+}
+
+impl From<Score> for SearchScore {
+    fn from(score: Score) -> SearchScore {
+        match (score.mate_in_moves(), score.centipawn_value()) {
+            (Some(moves), _) => SearchScore::Mate(moves),
+            (None, centipawns) => SearchScore::Centipawns(centipawns.unwrap_or_default()),
+        }
+    }
+}
+
+// In UCI terms, "cp 25" or "mate -3".
+impl fmt::Display for SearchScore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SearchScore::Centipawns(score) => write!(f, "cp {}", score),
+            SearchScore::Mate(moves) => write!(f, "mate {}", moves),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub best_move: Option<ChessMove>, // None if there are no legal moves.
+    pub ponder_move: Option<ChessMove>, // The reply the engine expects.
+    pub score: SearchScore,           // From the point of view of the side to move.
+    pub depth: usize,                 // Of the last completed iteration.
+    pub seldepth: usize,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    pub pv: Vec<ChessMove>, // The line the engine expects, starting with the best move.
+}
+
+// Iterative deepening, https://www.chessprogramming.org/Iterative_Deepening
+// Searches one ply deeper each iteration until a limit is reached, calling on_iteration after every completed one.
+// Only completed iterations are trusted, and the time and node limits can't stop the first so there is a move to play.
+// Setting stop from another thread ends the search as soon as possible, returning the best move found so far.
+//
+// Lazy SMP, https://www.chessprogramming.org/Lazy_SMP
+// Helper threads run the same search on their own, only sharing what they find through the transposition table.
+// The main thread owns the limits and the result, the helpers stop when it's done.
+//
+// The game history is the hash of every position before this one, oldest first, for the threefold repetition rule.
+// It only needs to go back to the last capture or pawn move.
+pub fn search(
+    board: &Board,
+    game_history: &[u64],
     tables: &ChessTables,
-) -> u16 {
-    let mut scores: Vec<JoinHandle<i32>> = Vec::new();
+    transposition_table: &TranspositionTable,
+    limits: &SearchLimits,
+    stop: &AtomicBool,
+    on_iteration: impl FnMut(&SearchResult),
+) -> SearchResult {
+    let context = SearchContext {
+        tables,
+        transposition_table,
+        time: match limits.infinite {
+            true => TimeManager::infinite(),
+            false => TimeManager::new(&limits.time_control, board.turn),
+        },
+        node_limit: limits.nodes,
+        nodes: AtomicU64::new(0),
+        stop,
+        finished: AtomicBool::new(false),
+        parameters: limits.parameters,
+    };
+    let max_depth = match (limits.depth, limits.mate) {
+        (Some(depth), _) => depth,
+        (None, Some(mate)) => (2 * mate as usize).saturating_sub(1), // Our moves and the replies in between.
+        (None, None) => MAX_PLY,
+    }
+    .clamp(1, MAX_PLY - 1);
+
     let mut move_data = board.get_all_legal_moves(tables);
     move_data.move_buffer.sort_unstable();
     move_data.move_buffer.reverse();
+    let root_moves: Vec<u16> = move_data.move_buffer[..move_data.length as usize]
+        .iter()
+        .copied()
+        .filter(|chess_move| {
+            limits.searchmoves.is_empty() || limits.searchmoves.contains(chess_move)
+        })
+        .collect();
+    if root_moves.is_empty() {
+        return SearchResult {
+            best_move: None,
+            ponder_move: None,
+            score: SearchScore::Centipawns(0),
+            depth: 0,
+            seldepth: 0,
+            nodes: 0,
+            elapsed: Duration::ZERO,
+            pv: Vec::new(),
+        };
+    }
+
+    transposition_table.new_search();
+    thread::scope(|scope| {
+        for helper in 1..limits.threads.max(1) {
+            let context = &context;
+            let mut root_moves = root_moves.clone();
+            scope.spawn(move || {
+                let mut search =
+                    SearchThread::new(context, RepetitionStack::new(game_history, board.hash));
+                // Half the helpers stay a ply ahead, so the threads aren't all searching the same thing.
+                for depth in 1 + helper % 2..=max_depth {
+                    search_root(
+                        depth - 1,
+                        board,
+                        &mut root_moves,
+                        (-Score::INFINITE, Score::INFINITE),
+                        &mut search,
+                    );
+                    if search.stopped {
+                        break;
+                    }
+                }
+            });
+        }
 
-    let mut move_scores = Vec::new();
+        let result = iterative_deepening(
+            board,
+            game_history,
+            &context,
+            limits,
+            max_depth,
+            root_moves,
+            on_iteration,
+        );
+        context.finished.store(true, Ordering::Relaxed);
+        result
+    })
+}
 
-    for possible_move in 0..move_data.length {
-        let history_clone = move_history.clone();
+// The main thread's search, see above.
+fn iterative_deepening(
+    board: &Board,
+    game_history: &[u64],
+    context: &SearchContext,
+    limits: &SearchLimits,
+    max_depth: usize,
+    mut root_moves: Vec<u16>,
+    mut on_iteration: impl FnMut(&SearchResult),
+) -> SearchResult {
+    let mut search = SearchThread::new(context, RepetitionStack::new(game_history, board.hash));
+    let mut result = SearchResult {
+        best_move: Some(ChessMove::unpack(root_moves[0])),
+        ponder_move: None,
+        score: SearchScore::Centipawns(0),
+        depth: 0,
+        seldepth: 0,
+        nodes: 0,
+        elapsed: Duration::ZERO,
+        pv: Vec::new(),
+    };
+    let mut previous_score = Score::DRAW;
 
-        let legal_move = move_data.move_buffer[possible_move as usize];
-        let new_board = board.move_piece(legal_move);
+    for depth in 1..=max_depth {
+        if depth > 1 && !context.time.should_start_iteration() {
+            break;
+        }
+        search.interruptible = depth > 1;
+        search.seldepth = 0;
 
-        let tables_clone = tables.clone();
-        move_scores.push(thread::spawn(move || {
-            -negamax(
-                0,
-                depth,
-                new_board,
-                history_clone,
-                -LARGE_VALUE_SAFE, // Min on maximizing player's turn
-                LARGE_VALUE_SAFE,  // Max on maximizing player's turn
-                &tables_clone,
-            )
-        }));
-    }
-
-    let mut best_score = i32::MIN;
-    let mut best_move_index = 0;
-    println!("----------------------------");
-    move_scores.reverse();
-    for index in 0..move_scores.len() {
-        let score = move_scores.pop().unwrap().join().unwrap();
-        let chess_move = ChessMove::unpack(move_data.move_buffer[index]);
-        let text = format!(
-            "{}{}",
-            human_readable_position(chess_move.origin),
-            human_readable_position(chess_move.destination)
-        );
-        println!("{}: {}", text, score);
-        if score > best_score {
-            best_score = score;
-            best_move_index = index;
-        }
-    }
-    println!("----------------------------");
-
-    println!("{}", best_move_index);
-    move_data.move_buffer[best_move_index]
-}
-
-pub fn evaluate(board: &Board, tables: &ChessTables) -> i32 {
-    let mut white_value = 0;
-    white_value += board.bitboards[Color::White as usize][Pieces::Queen as usize].popcnt() as i32
-        * QUEEN_VALUE;
-    white_value +=
-        board.bitboards[Color::White as usize][Pieces::Rook as usize].popcnt() as i32 * ROOK_VALUE;
-    white_value += board.bitboards[Color::White as usize][Pieces::Bishop as usize].popcnt() as i32
-        * BISHOP_VALUE;
-    white_value += board.bitboards[Color::White as usize][Pieces::Knight as usize].popcnt() as i32
-        * KNIGHT_VALUE;
-    white_value +=
-        board.bitboards[Color::White as usize][Pieces::Pawn as usize].popcnt() as i32 * PAWN_VALUE;
-
-    let mut black_value = 0;
-    black_value += board.bitboards[Color::Black as usize][Pieces::Queen as usize].popcnt() as i32
-        * QUEEN_VALUE;
-    black_value +=
-        board.bitboards[Color::Black as usize][Pieces::Rook as usize].popcnt() as i32 * ROOK_VALUE;
-    black_value += board.bitboards[Color::Black as usize][Pieces::Bishop as usize].popcnt() as i32
-        * BISHOP_VALUE;
-    black_value += board.bitboards[Color::Black as usize][Pieces::Knight as usize].popcnt() as i32
-        * KNIGHT_VALUE;
-    black_value +=
-        board.bitboards[Color::Black as usize][Pieces::Pawn as usize].popcnt() as i32 * PAWN_VALUE;
-
-    let white_mobility_value =
-        board.get_full_capture_mask(Color::White, tables).popcnt() as i32 * MOBILITY_VALUE;
-    let black_mobility_value =
-        board.get_full_capture_mask(Color::Black, tables).popcnt() as i32 * MOBILITY_VALUE;
-    match board.turn {
-        Color::White => (white_value - black_value) + (white_mobility_value - black_mobility_value),
-        Color::Black => (black_value - white_value) + (black_mobility_value - white_mobility_value),
+        // Aspiration windows, https://www.chessprogramming.org/Aspiration_Windows
+        // The score rarely moves far between iterations, and a narrow window cuts off more.
+        // When the score falls outside, widen that side and search again.
+        let mut delta = ASPIRATION_WINDOW;
+        let mut window = match depth >= ASPIRATION_MIN_DEPTH && !previous_score.is_mate() {
+            true => (previous_score - delta, previous_score + delta),
+            false => (-Score::INFINITE, Score::INFINITE),
+        };
+        let root_search = loop {
+            let root_search = search_root(depth - 1, board, &mut root_moves, window, &mut search);
+            if !root_search.completed {
+                break root_search;
+            }
+            delta *= 2;
+            if root_search.score <= window.0 {
+                window.0 = match root_search.score.is_mate() || delta > MAX_ASPIRATION_WINDOW {
+                    true => -Score::INFINITE,
+                    false => root_search.score - delta,
+                };
+            } else if root_search.score >= window.1 {
+                window.1 = match root_search.score.is_mate() || delta > MAX_ASPIRATION_WINDOW {
+                    true => Score::INFINITE,
+                    false => root_search.score + delta,
+                };
+            } else {
+                break root_search;
+            }
+        };
+        if !root_search.completed {
+            // Stopped during the first iteration, the root moves that did finish are still better than nothing.
+            if result.depth == 0 && !root_search.pv.is_empty() {
+                result.best_move = Some(ChessMove::unpack(root_search.pv[0]));
+                result.pv = root_search.pv.into_iter().map(ChessMove::unpack).collect();
+            }
+            break;
+        }
+        previous_score = root_search.score;
+
+        result.best_move = Some(ChessMove::unpack(root_search.pv[0]));
+        result.ponder_move = root_search.pv.get(1).copied().map(ChessMove::unpack);
+        result.score = root_search.score.into();
+        result.depth = depth;
+        result.seldepth = search.seldepth;
+        result.nodes = context.nodes.load(Ordering::Relaxed);
+        result.elapsed = context.time.elapsed();
+        result.pv = root_search.pv.into_iter().map(ChessMove::unpack).collect();
+        on_iteration(&result);
+
+        if let SearchScore::Mate(moves) = result.score {
+            if limits
+                .mate
+                .is_some_and(|mate| moves > 0 && moves <= mate as i32)
+            {
+                break;
+            }
+            let mate_ply = root_search.score.mate_ply().unwrap_or_default();
+            if moves > 0 && mate_ply < depth as i32 {
+                break; // Every line this short has been searched, deeper searches can't find a faster mate.
+            }
+        }
+        if limits
+            .nodes
+            .is_some_and(|limit| context.nodes.load(Ordering::Relaxed) >= limit)
+        {
+            break;
+        }
+    }
+
+    result.nodes = context.nodes.load(Ordering::Relaxed);
+    result.elapsed = context.time.elapsed();
+    result
+}
+
+// Fixed positions used by the bench command and the criterion benches, covering openings, tactics, castling, promotions and endgames.
+pub const BENCH_POSITIONS: [&str; 8] = [
+    STARTING_POSITION_FEN,
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1",
+    "8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1",
+];
+
+pub const BENCH_DEPTH: usize = 4;
+const BENCH_HASH_MB: usize = 16;
+
+pub struct BenchResult {
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+// Searches a single position to a fixed depth, counted the same way as "go depth", returning the number of nodes visited.
+// Uses one thread and an empty transposition table so the node count is always the same.
+pub fn bench_position(depth: usize, board: &Board, tables: &ChessTables) -> u64 {
+    let mut move_data = board.get_all_legal_moves(tables);
+    move_data.move_buffer.sort_unstable();
+    move_data.move_buffer.reverse();
+    let root_moves = &mut move_data.move_buffer[..move_data.length as usize];
+    let transposition_table = TranspositionTable::new(BENCH_HASH_MB);
+    let context = SearchContext {
+        tables,
+        transposition_table: &transposition_table,
+        time: TimeManager::infinite(),
+        node_limit: None,
+        nodes: AtomicU64::new(0),
+        stop: &AtomicBool::new(false),
+        finished: AtomicBool::new(false),
+        parameters: SearchParameters::default(),
+    };
+    search_root(
+        depth.saturating_sub(1),
+        board,
+        root_moves,
+        (-Score::INFINITE, Score::INFINITE),
+        &mut SearchThread::new(&context, RepetitionStack::new(&[], board.hash)),
+    );
+    context.nodes.into_inner()
+}
+
+// The node count is deterministic, so it acts as a signature: any change to it means the search changed.
+pub fn bench(depth: usize, tables: &ChessTables) -> BenchResult {
+    let start = Instant::now();
+    let mut nodes = 0;
+    for fen in BENCH_POSITIONS {
+        nodes += bench_position(depth, &fen_parser(fen), tables);
+    }
+    BenchResult {
+        nodes,
+        elapsed: start.elapsed(),
     }
 }
@@ -0,0 +1,286 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::{
+        chess_data::fen_parser,
+        constants::STARTING_POSITION_FEN,
+        engine::{search, SearchLimits, SearchParameters, SearchResult, SearchScore},
+        transposition::{TranspositionTable, DEFAULT_HASH_MB},
+        ChessMove, ChessTables,
+    };
+
+    fn search_fen(fen: &str, limits: &SearchLimits) -> SearchResult {
+        let tables = ChessTables::default();
+        let board = fen_parser(fen);
+        search(
+            &board,
+            &[],
+            &tables,
+            &TranspositionTable::new(DEFAULT_HASH_MB),
+            limits,
+            &AtomicBool::new(false),
+            |_| {},
+        )
+    }
+
+    fn depth(depth: usize) -> SearchLimits {
+        SearchLimits {
+            depth: Some(depth),
+            infinite: true,
+            ..Default::default()
+        }
+    }
+
+    fn best_move(fen: &str, depth_limit: usize) -> String {
+        search_fen(fen, &depth(depth_limit))
+            .best_move
+            .unwrap()
+            .to_uci()
+    }
+
+    #[test]
+    fn engine_mate_in_one() {
+        assert_eq!(
+            best_move("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1", 3),
+            "d1d8"
+        );
+    }
+
+    #[test]
+    fn engine_quiescence() {
+        // The pawn on d5 is defended, a one ply search without quiescence would take it with the queen.
+        assert_ne!(best_move("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1", 1), "d1d5");
+        // This one isn't.
+        assert_eq!(best_move("4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1", 1), "d1d5");
+    }
+
+    #[test]
+    fn engine_principal_variation() {
+        // Mate in two, Rd8+ Rxd8 Rxd8#
+        let result = search_fen("r5k1/5ppp/8/8/8/8/3R1PPP/3R2K1 w - - 0 1", &depth(5));
+        assert_eq!(result.score, SearchScore::Mate(2));
+        assert_eq!(result.score.to_string(), "mate 2");
+        assert_eq!(result.depth, 3); // Deeper searches can't find a faster mate.
+
+        let pv: Vec<String> = result.pv.iter().map(ChessMove::to_uci).collect();
+        assert_eq!(pv.len(), 3);
+        assert_eq!(Some(result.pv[0]), result.best_move);
+        assert_eq!(Some(result.pv[1]), result.ponder_move);
+        assert_eq!(pv[1], "a8d8");
+        assert!(pv[2] == "d1d8" || pv[2] == "d2d8");
+    }
+
+    #[test]
+    fn engine_search_limits() {
+        let fen = "r5k1/5ppp/8/8/8/8/3R1PPP/3R2K1 w - - 0 1";
+
+        // Stops as soon as the mate is found, without a depth limit.
+        let limits = SearchLimits {
+            mate: Some(2),
+            infinite: true,
+            ..Default::default()
+        };
+        assert_eq!(search_fen(fen, &limits).score, SearchScore::Mate(2));
+        // There's no mate in 0, but asking for one still searches a ply instead of underflowing.
+        let limits = SearchLimits {
+            mate: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(search_fen(fen, &limits).depth, 1);
+
+        // Only the listed root moves are searched.
+        let board = fen_parser(fen);
+        let tables = ChessTables::default();
+        let legal_moves = board.get_all_legal_moves(&tables);
+        let king_move = *legal_moves.move_buffer[..legal_moves.length as usize]
+            .iter()
+            .find(|&&chess_move| ChessMove::unpack(chess_move).to_uci() == "g1f1")
+            .unwrap();
+        let limits = SearchLimits {
+            searchmoves: vec![king_move],
+            ..depth(3)
+        };
+        let result = search_fen(fen, &limits);
+        assert_eq!(result.best_move.unwrap().to_uci(), "g1f1");
+        assert!(matches!(result.score, SearchScore::Centipawns(_)));
+
+        // A node limit still completes the first iteration.
+        let limits = SearchLimits {
+            nodes: Some(1),
+            infinite: true,
+            ..Default::default()
+        };
+        let result = search_fen(fen, &limits);
+        assert_eq!(result.depth, 1);
+        assert!(result.best_move.is_some());
+
+        // No legal moves, stalemate.
+        let result = search_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", &depth(3));
+        assert_eq!(result.best_move, None);
+    }
+
+    #[test]
+    fn engine_stop() {
+        let tables = ChessTables::default();
+        let board = fen_parser(STARTING_POSITION_FEN);
+        let transposition_table = TranspositionTable::new(DEFAULT_HASH_MB);
+        let limits = SearchLimits {
+            infinite: true,
+            ..Default::default()
+        };
+
+        // Stopped before it starts, there is still a move to play.
+        let stop = AtomicBool::new(true);
+        let result = search(
+            &board,
+            &[],
+            &tables,
+            &transposition_table,
+            &limits,
+            &stop,
+            |_| {},
+        );
+        assert_eq!(result.depth, 0);
+        assert!(result.best_move.is_some());
+
+        // An infinite search only ends when it's stopped from another thread.
+        let stop = AtomicBool::new(false);
+        let result = thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(200));
+                stop.store(true, Ordering::Relaxed);
+            });
+            search(
+                &board,
+                &[],
+                &tables,
+                &transposition_table,
+                &limits,
+                &stop,
+                |_| {},
+            )
+        });
+        assert!(result.depth >= 1);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn engine_lazy_smp() {
+        let limits = SearchLimits {
+            threads: 4,
+            ..depth(5)
+        };
+        let result = search_fen("r5k1/5ppp/8/8/8/8/3R1PPP/3R2K1 w - - 0 1", &limits);
+        assert_eq!(result.score, SearchScore::Mate(2));
+        assert!(result.pv.len() >= 2 && result.pv[1].to_uci() == "a8d8");
+
+        let limits = SearchLimits {
+            threads: 4,
+            ..depth(3)
+        };
+        assert_eq!(
+            search_fen("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1", &limits)
+                .best_move
+                .unwrap()
+                .to_uci(),
+            "d1d8"
+        );
+    }
+
+    #[test]
+    fn engine_selective_search() {
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let full_width = SearchLimits {
+            parameters: SearchParameters::none(),
+            ..depth(4)
+        };
+        let selective = search_fen(kiwipete, &depth(4));
+        assert!(selective.nodes < search_fen(kiwipete, &full_width).nodes);
+
+        // Each technique on its own still finds the mate.
+        let all_off = SearchParameters::none();
+        for parameters in [
+            SearchParameters {
+                principal_variation_search: true,
+                ..all_off
+            },
+            SearchParameters {
+                null_move_pruning: true,
+                ..all_off
+            },
+            SearchParameters {
+                late_move_reductions: true,
+                ..all_off
+            },
+            SearchParameters {
+                reverse_futility_pruning: true,
+                ..all_off
+            },
+            SearchParameters {
+                futility_pruning: true,
+                ..all_off
+            },
+        ] {
+            let limits = SearchLimits {
+                parameters,
+                ..depth(5)
+            };
+            let result = search_fen("r5k1/5ppp/8/8/8/8/3R1PPP/3R2K1 w - - 0 1", &limits);
+            assert_eq!(result.score, SearchScore::Mate(2), "{:?}", parameters);
+        }
+    }
+
+    #[test]
+    fn engine_extensions() {
+        // Mate in three with checks all the way, Qxh8+ Kxh8 Bf6+ Kg8 Re8#
+        let fen = "r1b3kr/ppp1Bp1p/1b6/n2P4/2p3q1/2Q2N2/P4PPP/RN2R1K1 w - - 1 1";
+        let result = search_fen(fen, &depth(3));
+        assert_eq!(result.score, SearchScore::Mate(3));
+        assert_eq!(result.best_move.unwrap().to_uci(), "c3h8");
+
+        // Without extending the checks it's past the horizon.
+        let limits = SearchLimits {
+            parameters: SearchParameters {
+                check_extensions: false,
+                ..Default::default()
+            },
+            ..depth(3)
+        };
+        assert!(matches!(
+            search_fen(fen, &limits).score,
+            SearchScore::Centipawns(_)
+        ));
+    }
+
+    #[test]
+    fn engine_threefold_repetition() {
+        // A queen down, Kg1 is the only move.
+        let tables = ChessTables::default();
+        let board = fen_parser("k7/8/8/8/8/8/1q6/7K w - - 0 1");
+        let after_move = board.move_piece(
+            board.get_all_legal_moves(&tables).move_buffer[0], // Kg1
+        );
+        let limits = depth(2);
+        let run = |game_history: &[u64]| {
+            search(
+                &board,
+                game_history,
+                &tables,
+                &TranspositionTable::new(DEFAULT_HASH_MB),
+                &limits,
+                &AtomicBool::new(false),
+                |_| {},
+            )
+        };
+
+        assert!(matches!(run(&[]).score, SearchScore::Centipawns(score) if score < -500));
+        // After Kg1 has been on the board twice, playing it again is a draw.
+        let game_history = [after_move.hash, 10, after_move.hash, 11, 12];
+        assert_eq!(run(&game_history).score, SearchScore::Centipawns(0));
+        // Once isn't enough.
+        assert_ne!(run(&game_history[2..]).score, SearchScore::Centipawns(0));
+    }
+}
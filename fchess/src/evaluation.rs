@@ -0,0 +1,293 @@
+// Static evaluation, https://www.chessprogramming.org/Tapered_Eval
+// Every term has a midgame and an endgame value, blended by how much material is left on the board.
+// Material and piece-square scores only depend on where the pieces are, so move_piece keeps them up to date.
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use crate::king_safety::evaluate_king_safety;
+use crate::pawns::{evaluate_pawns, PawnTable};
+use crate::pieces::evaluate_pieces;
+use crate::{Board, ChessTables, Color, Pieces};
+
+// Knights and bishops count 1, rooks 2 and queens 4, so the starting position is a full midgame.
+pub const MAX_PHASE: i32 = 24;
+const PHASE_VALUES: [i32; 6] = [0, 4, 2, 1, 1, 0];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TaperedScore {
+    pub midgame: i32,
+    pub endgame: i32,
+}
+
+impl TaperedScore {
+    pub const fn new(midgame: i32, endgame: i32) -> TaperedScore {
+        TaperedScore { midgame, endgame }
+    }
+
+    // Promotions can push the phase past the maximum, that's still a midgame.
+    pub fn taper(self, phase: i32) -> i32 {
+        let phase = phase.min(MAX_PHASE);
+        (self.midgame * phase + self.endgame * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for TaperedScore {
+    type Output = TaperedScore;
+
+    fn add(self, other: TaperedScore) -> TaperedScore {
+        TaperedScore::new(self.midgame + other.midgame, self.endgame + other.endgame)
+    }
+}
+
+impl Sub for TaperedScore {
+    type Output = TaperedScore;
+
+    fn sub(self, other: TaperedScore) -> TaperedScore {
+        TaperedScore::new(self.midgame - other.midgame, self.endgame - other.endgame)
+    }
+}
+
+impl Neg for TaperedScore {
+    type Output = TaperedScore;
+
+    fn neg(self) -> TaperedScore {
+        TaperedScore::new(-self.midgame, -self.endgame)
+    }
+}
+
+// For terms that are counted, e.g. the number of doubled pawns.
+impl Mul<i32> for TaperedScore {
+    type Output = TaperedScore;
+
+    fn mul(self, count: i32) -> TaperedScore {
+        TaperedScore::new(self.midgame * count, self.endgame * count)
+    }
+}
+
+impl AddAssign for TaperedScore {
+    fn add_assign(&mut self, other: TaperedScore) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for TaperedScore {
+    fn sub_assign(&mut self, other: TaperedScore) {
+        *self = *self - other;
+    }
+}
+
+// Values and tables from PeSTO, https://www.chessprogramming.org/PeSTO%27s_Evaluation_Function
+// In the same order as Pieces: king, queen, rook, bishop, knight, pawn.
+const MIDGAME_VALUES: [i32; 6] = [0, 1025, 477, 365, 337, 82];
+const ENDGAME_VALUES: [i32; 6] = [0, 936, 512, 297, 281, 94];
+
+// The tables are laid out the way the board looks from white's side, A8 first and H1 last.
+#[rustfmt::skip]
+const MIDGAME_TABLES: [[i32; 64]; 6] = [
+    [
+        -65,  23,  16, -15, -56, -34,   2,  13,
+         29,  -1, -20,  -7,  -8,  -4, -38, -29,
+         -9,  24,   2, -16, -20,   6,  22, -22,
+        -17, -20, -12, -27, -30, -25, -14, -36,
+        -49,  -1, -27, -39, -46, -44, -33, -51,
+        -14, -14, -22, -46, -44, -30, -15, -27,
+          1,   7,  -8, -64, -43, -16,   9,   8,
+        -15,  36,  12, -54,   8, -28,  24,  14,
+    ],
+    [
+        -28,   0,  29,  12,  59,  44,  43,  45,
+        -24, -39,  -5,   1, -16,  57,  28,  54,
+        -13, -17,   7,   8,  29,  56,  47,  57,
+        -27, -27, -16, -16,  -1,  17,  -2,   1,
+         -9, -26,  -9, -10,  -2,  -4,   3,  -3,
+        -14,   2, -11,  -2,  -5,   2,  14,   5,
+        -35,  -8,  11,   2,   8,  15,  -3,   1,
+         -1, -18,  -9,  10, -15, -25, -31, -50,
+    ],
+    [
+         32,  42,  32,  51,  63,   9,  31,  43,
+         27,  32,  58,  62,  80,  67,  26,  44,
+         -5,  19,  26,  36,  17,  45,  61,  16,
+        -24, -11,   7,  26,  24,  35,  -8, -20,
+        -36, -26, -12,  -1,   9,  -7,   6, -23,
+        -45, -25, -16, -17,   3,   0,  -5, -33,
+        -44, -16, -20,  -9,  -1,  11,  -6, -71,
+        -19, -13,   1,  17,  16,   7, -37, -26,
+    ],
+    [
+        -29,   4, -82, -37, -25, -42,   7,  -8,
+        -26,  16, -18, -13,  30,  59,  18, -47,
+        -16,  37,  43,  40,  35,  50,  37,  -2,
+         -4,   5,  19,  50,  37,  37,   7,  -2,
+         -6,  13,  13,  26,  34,  12,  10,   4,
+          0,  15,  15,  15,  14,  27,  18,  10,
+          4,  15,  16,   0,   7,  21,  33,   1,
+        -33,  -3, -14, -21, -13, -12, -39, -21,
+    ],
+    [
+       -167, -89, -34, -49,  61, -97, -15,-107,
+        -73, -41,  72,  36,  23,  62,   7, -17,
+        -47,  60,  37,  65,  84, 129,  73,  44,
+         -9,  17,  19,  53,  37,  69,  18,  22,
+        -13,   4,  16,  13,  28,  19,  21,  -8,
+        -23,  -9,  12,  10,  19,  17,  25, -16,
+        -29, -53, -12,  -3,  -1,  18, -14, -19,
+       -105, -21, -58, -33, -17, -28, -19, -23,
+    ],
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+         98, 134,  61,  95,  68, 126,  34, -11,
+         -6,   7,  26,  31,  65,  56,  25, -20,
+        -14,  13,   6,  21,  23,  12,  17, -23,
+        -27,  -2,  -5,  12,  17,   6,  10, -25,
+        -26,  -4,  -4, -10,   3,   3,  33, -12,
+        -35,  -1, -20, -23, -15,  24,  38, -22,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+];
+
+#[rustfmt::skip]
+const ENDGAME_TABLES: [[i32; 64]; 6] = [
+    [
+        -74, -35, -18, -18, -11,  15,   4, -17,
+        -12,  17,  14,  17,  17,  38,  23,  11,
+         10,  17,  23,  15,  20,  45,  44,  13,
+         -8,  22,  24,  27,  26,  33,  26,   3,
+        -18,  -4,  21,  24,  27,  23,   9, -11,
+        -19,  -3,  11,  21,  23,  16,   7,  -9,
+        -27, -11,   4,  13,  14,   4,  -5, -17,
+        -53, -34, -21, -11, -28, -14, -24, -43,
+    ],
+    [
+         -9,  22,  22,  27,  27,  19,  10,  20,
+        -17,  20,  32,  41,  58,  25,  30,   0,
+        -20,   6,   9,  49,  47,  35,  19,   9,
+          3,  22,  24,  45,  57,  40,  57,  36,
+        -18,  28,  19,  47,  31,  34,  39,  23,
+        -16, -27,  15,   6,   9,  17,  10,   5,
+        -22, -23, -30, -16, -16, -23, -36, -32,
+        -33, -28, -22, -43,  -5, -32, -20, -41,
+    ],
+    [
+         13,  10,  18,  15,  12,  12,   8,   5,
+         11,  13,  13,  11,  -3,   3,   8,   3,
+          7,   7,   7,   5,   4,  -3,  -5,  -3,
+          4,   3,  13,   1,   2,   1,  -1,   2,
+          3,   5,   8,   4,  -5,  -6,  -8, -11,
+         -4,   0,  -5,  -1,  -7, -12,  -8, -16,
+         -6,  -6,   0,   2,  -9,  -9, -11,  -3,
+         -9,   2,   3,  -1,  -5, -13,   4, -20,
+    ],
+    [
+        -14, -21, -11,  -8,  -7,  -9, -17, -24,
+         -8,  -4,   7, -12,  -3, -13,  -4, -14,
+          2,  -8,   0,  -1,  -2,   6,   0,   4,
+         -3,   9,  12,   9,  14,  10,   3,   2,
+         -6,   3,  13,  19,   7,  10,  -3,  -9,
+        -12,  -3,   8,  10,  13,   3,  -7, -15,
+        -14, -18,  -7,  -1,   4,  -9, -15, -27,
+        -23,  -9, -23,  -5,  -9, -16,  -5, -17,
+    ],
+    [
+        -58, -38, -13, -28, -31, -27, -63, -99,
+        -25,  -8, -25,  -2,  -9, -25, -24, -52,
+        -24, -20,  10,   9,  -1,  -9, -19, -41,
+        -17,   3,  22,  22,  22,  11,   8, -18,
+        -18,  -6,  16,  25,  16,  17,   4, -18,
+        -23,  -3,  -1,  15,  10,  -3, -20, -22,
+        -42, -20, -10,  -5,  -2, -20, -23, -44,
+        -29, -51, -23, -15, -22, -18, -50, -64,
+    ],
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+        178, 173, 158, 134, 147, 132, 165, 187,
+         94, 100,  85,  67,  56,  53,  82,  84,
+         32,  24,  13,   5,  -2,   4,  17,  17,
+         13,   9,  -3,  -7,  -7,  -8,   3,  -1,
+          4,   7,  -6,   1,   0,  -5,  -1,  -8,
+         13,   8,   8,  10,  13,   0,   2,  -7,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+];
+
+// Material and position folded into one table, indexed by the board's own squares and from white's side.
+// H1 is square 0, so white reads the layout above backwards and black reads it mirrored across the ranks.
+const fn generate_piece_square_tables() -> [[[TaperedScore; 64]; 6]; 2] {
+    let mut tables = [[[TaperedScore::new(0, 0); 64]; 6]; 2];
+    let mut piece = 0;
+    while piece < 6 {
+        let mut position = 0;
+        while position < 64 {
+            let white = 63 - position;
+            let black = position ^ 7;
+            tables[Color::White as usize][piece][position] = TaperedScore::new(
+                MIDGAME_VALUES[piece] + MIDGAME_TABLES[piece][white],
+                ENDGAME_VALUES[piece] + ENDGAME_TABLES[piece][white],
+            );
+            tables[Color::Black as usize][piece][position] = TaperedScore::new(
+                -(MIDGAME_VALUES[piece] + MIDGAME_TABLES[piece][black]),
+                -(ENDGAME_VALUES[piece] + ENDGAME_TABLES[piece][black]),
+            );
+            position += 1;
+        }
+        piece += 1;
+    }
+    tables
+}
+
+static PIECE_SQUARE_TABLES: [[[TaperedScore; 64]; 6]; 2] = generate_piece_square_tables();
+
+#[inline]
+pub fn piece_square(color: Color, piece: Pieces, position: u8) -> TaperedScore {
+    PIECE_SQUARE_TABLES[color as usize][piece as usize][position as usize]
+}
+
+#[inline]
+pub fn phase_value(piece: Pieces) -> i32 {
+    PHASE_VALUES[piece as usize]
+}
+
+impl Board {
+    // From scratch, the incremental scores in move_piece should always match these.
+    pub fn compute_piece_square(&self) -> TaperedScore {
+        let mut score = TaperedScore::default();
+        for color in [Color::White, Color::Black] {
+            for piece in [
+                Pieces::King,
+                Pieces::Queen,
+                Pieces::Rook,
+                Pieces::Bishop,
+                Pieces::Knight,
+                Pieces::Pawn,
+            ] {
+                let mut bitboard = self.bitboards[color as usize][piece as usize];
+                while !bitboard.is_empty() {
+                    score += piece_square(color, piece, bitboard.get_index_and_pop());
+                }
+            }
+        }
+        score
+    }
+
+    pub fn compute_phase(&self) -> i32 {
+        let mut phase = 0;
+        for color in 0..2 {
+            for (piece, value) in PHASE_VALUES.iter().enumerate() {
+                phase += self.bitboards[color][piece].popcnt() as i32 * value;
+            }
+        }
+        phase
+    }
+}
+
+// From the side to move's point of view.
+pub fn evaluate(board: &Board, tables: &ChessTables, pawn_table: &mut PawnTable) -> i32 {
+    let score = board.piece_square
+        + evaluate_pawns(board, pawn_table)
+        + evaluate_king_safety(board, tables)
+        + evaluate_pieces(board, tables);
+    let score = score.taper(board.phase);
+    match board.turn {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
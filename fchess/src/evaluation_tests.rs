@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chess_data::fen_parser,
+        evaluation::{evaluate, TaperedScore, MAX_PHASE},
+        pawns::PawnTable,
+        Board, ChessTables, STARTING_POSITION_FEN,
+    };
+
+    #[test]
+    fn game_phase() {
+        assert_eq!(Board::default().phase, MAX_PHASE);
+        assert_eq!(fen_parser(STARTING_POSITION_FEN).phase, MAX_PHASE);
+        assert_eq!(
+            fen_parser("4k3/pppppppp/8/8/8/8/PPPPPPPP/4K3 w - - 0 1").phase,
+            0
+        );
+        assert_eq!(fen_parser("4k3/8/8/8/8/8/8/R2QK3 w - - 0 1").phase, 6);
+
+        let score = TaperedScore::new(100, -100);
+        assert_eq!(score.taper(MAX_PHASE), 100);
+        assert_eq!(score.taper(0), -100);
+        assert_eq!(score.taper(MAX_PHASE / 2), 0);
+        assert_eq!(score.taper(MAX_PHASE + 4), 100); // Extra queens from promotions.
+    }
+
+    #[test]
+    fn piece_square_tables() {
+        let tables = ChessTables::default();
+        let mut pawn_table = PawnTable::default();
+        let start = fen_parser(STARTING_POSITION_FEN);
+        assert_eq!(start.piece_square, TaperedScore::default());
+        assert_eq!(evaluate(&start, &tables, &mut pawn_table), 0);
+
+        // Same material, but a knight in the center is worth more than one on the rim.
+        let center = fen_parser("4k3/8/8/8/4N3/8/8/4K3 w - - 0 1");
+        let rim = fen_parser("4k3/8/8/8/N7/8/8/4K3 w - - 0 1");
+        assert!(center.piece_square.midgame > rim.piece_square.midgame);
+        assert!(
+            evaluate(&center, &tables, &mut pawn_table) > evaluate(&rim, &tables, &mut pawn_table)
+        );
+
+        // With no pieces left the king belongs in the center, before that it belongs behind its pawns.
+        let central_king = fen_parser("4k3/8/8/8/3K4/8/8/8 w - - 0 1");
+        let castled_king = fen_parser("4k3/8/8/8/8/8/8/6K1 w - - 0 1");
+        assert!(central_king.piece_square.endgame > castled_king.piece_square.endgame);
+        assert!(central_king.piece_square.midgame < castled_king.piece_square.midgame);
+    }
+
+    #[test]
+    fn evaluation_is_from_side_to_move() {
+        let tables = ChessTables::default();
+        let mut pawn_table = PawnTable::default();
+        let white = fen_parser("4k3/8/8/8/8/8/8/3QK3 w - - 0 1");
+        let black = fen_parser("4k3/8/8/8/8/8/8/3QK3 b - - 0 1");
+        assert!(evaluate(&white, &tables, &mut pawn_table) > 800);
+        assert_eq!(
+            evaluate(&white, &tables, &mut pawn_table),
+            -evaluate(&black, &tables, &mut pawn_table)
+        );
+    }
+}
@@ -0,0 +1,121 @@
+// King safety, https://www.chessprogramming.org/King_Safety
+// Looks at the pawns in front of the king and the enemy pieces aiming at the squares around it.
+// Attacks are added up into danger units, which go through a table so a real attack costs far more than a lone piece.
+use crate::bitboard::BitBoard;
+use crate::constants::*;
+use crate::evaluation::TaperedScore;
+use crate::pawns::{forward_fill, forward_one};
+use crate::{Board, ChessTables, Color, LookupTable, Pieces};
+
+// Indexed by how many ranks in front of the king the closest pawn on a file is, 0 when there isn't one.
+const PAWN_SHIELD: [i32; 8] = [-20, 20, 10, 0, -5, -10, -10, -10];
+const PAWN_STORM: [i32; 8] = [0, -10, -30, -20, -10, -5, 0, 0];
+const SEMI_OPEN_KING_FILE: i32 = -15; // No pawn of ours on the file.
+const OPEN_KING_FILE: i32 = -25; // No pawns at all, a rook or queen can come straight down it.
+
+// Danger units for every square in the king zone a piece attacks, in the same order as Pieces.
+const ATTACK_WEIGHTS: [i32; 6] = [0, 8, 5, 3, 3, 0];
+// In percent, by the number of pieces attacking the zone. A single piece isn't much of an attack on its own.
+const ATTACKER_SCALE: [i32; 8] = [0, 0, 50, 75, 88, 94, 97, 99];
+const MAX_KING_DANGER: i32 = 500;
+
+const fn generate_safety_table() -> [i32; 100] {
+    let mut table = [0; 100];
+    let mut units = 0;
+    while units < 100 {
+        let danger = units as i32 * units as i32 / 2;
+        table[units] = if danger < MAX_KING_DANGER {
+            danger
+        } else {
+            MAX_KING_DANGER
+        };
+        units += 1;
+    }
+    table
+}
+
+static SAFETY_TABLE: [i32; 100] = generate_safety_table();
+
+// The closest pawn to the king in a set of pawns ahead of it, as a distance in ranks.
+fn closest_pawn_distance(color: Color, pawns: BitBoard, king_rank: u8) -> usize {
+    if pawns.is_empty() {
+        return 0;
+    }
+    let position = match color {
+        Color::White => pawns.0.trailing_zeros() as u8,
+        Color::Black => 63 - pawns.0.leading_zeros() as u8,
+    };
+    (position / 8).abs_diff(king_rank) as usize
+}
+
+// The shelter in front of the king, the pawns coming at it and the files next to it.
+fn king_shelter(board: &Board, color: Color, king_position: u8) -> i32 {
+    let pawns = board.bitboards[color as usize][Pieces::Pawn as usize];
+    let enemy_pawns = board.bitboards[color.opposite() as usize][Pieces::Pawn as usize];
+    let king_rank = king_position / 8;
+    let king_file = king_position % 8;
+    let ranks_ahead = forward_fill(
+        color,
+        forward_one(color, BitBoard(BOARD_BOTTOM.0 << (8 * king_rank))),
+    );
+
+    let mut score = 0;
+    for file in king_file.saturating_sub(1)..=(king_file + 1).min(7) {
+        let file_mask = BitBoard(BOARD_RIGHT.0 << file);
+        let shield = pawns & file_mask & ranks_ahead;
+        let storm = enemy_pawns & file_mask & ranks_ahead;
+        score += PAWN_SHIELD[closest_pawn_distance(color, shield, king_rank)];
+        score += PAWN_STORM[closest_pawn_distance(color, storm, king_rank)];
+
+        if (pawns & file_mask).is_empty() {
+            score += match (enemy_pawns & file_mask).is_empty() {
+                true => OPEN_KING_FILE,
+                false => SEMI_OPEN_KING_FILE,
+            };
+        }
+    }
+    score
+}
+
+// Danger from the enemy pieces attacking the squares around the king and the ones in front of those.
+fn king_danger(board: &Board, color: Color, king_position: u8, tables: &ChessTables) -> i32 {
+    let king = BitBoard(1 << king_position);
+    let king_moves = tables.lookup_tables[LookupTable::KingMoves as usize][king_position as usize];
+    let king_zone = king | king_moves | forward_one(color, king_moves);
+
+    let enemy = color.opposite();
+    let mut attackers = 0;
+    let mut units = 0;
+    for piece in [Pieces::Queen, Pieces::Rook, Pieces::Bishop, Pieces::Knight] {
+        let mut pieces = board.bitboards[enemy as usize][piece as usize];
+        while !pieces.is_empty() {
+            let position = pieces.get_index_and_pop();
+            let attacks = board
+                .get_pseudolegal_capture_mask(position, enemy, tables)
+                .0
+                & king_zone;
+            if !attacks.is_empty() {
+                attackers += 1;
+                units += ATTACK_WEIGHTS[piece as usize] * attacks.popcnt() as i32;
+            }
+        }
+    }
+
+    let units = units * ATTACKER_SCALE[attackers.min(7)] / 100;
+    SAFETY_TABLE[units.min(99) as usize]
+}
+
+fn side_safety(board: &Board, color: Color, tables: &ChessTables) -> TaperedScore {
+    let king_position = board.bitboards[color as usize][Pieces::King as usize]
+        .0
+        .trailing_zeros() as u8;
+    let shelter = king_shelter(board, color, king_position);
+    let danger = king_danger(board, color, king_position, tables);
+    // Pawn cover only matters while there are pieces around to attack the king.
+    TaperedScore::new(shelter - danger, -danger / 4)
+}
+
+// From white's side.
+pub fn evaluate_king_safety(board: &Board, tables: &ChessTables) -> TaperedScore {
+    side_safety(board, Color::White, tables) - side_safety(board, Color::Black, tables)
+}
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chess_data::fen_parser, evaluation::TaperedScore, king_safety::evaluate_king_safety,
+        ChessTables, STARTING_POSITION_FEN,
+    };
+
+    fn king_safety(fen: &str) -> TaperedScore {
+        evaluate_king_safety(&fen_parser(fen), &ChessTables::default())
+    }
+
+    #[test]
+    fn pawn_shelter() {
+        assert_eq!(king_safety(STARTING_POSITION_FEN), TaperedScore::default());
+
+        let castled = king_safety("r4rk1/5ppp/8/8/8/8/5PPP/R4RK1 w - - 0 1");
+        assert_eq!(castled, TaperedScore::default());
+
+        // White pushed the g pawn, or lost it and left the file half open.
+        let pushed = king_safety("r4rk1/5ppp/8/8/8/6P1/5P1P/R4RK1 w - - 0 1");
+        let semi_open = king_safety("r4rk1/5ppp/8/8/8/8/5P1P/R4RK1 w - - 0 1");
+        assert!(pushed.midgame < castled.midgame);
+        assert!(semi_open.midgame < pushed.midgame);
+        // Both sides have the same hole, so it evens out.
+        let open = king_safety("r4rk1/5p1p/8/8/8/8/5P1P/R4RK1 w - - 0 1");
+        assert_eq!(open, TaperedScore::default());
+
+        let storm = king_safety("r4rk1/8/8/8/6pp/8/5PPP/R4RK1 w - - 0 1");
+        let no_storm = king_safety("r4rk1/8/8/8/8/8/5PPP/R4RK1 w - - 0 1");
+        assert!(storm.midgame < no_storm.midgame);
+    }
+
+    #[test]
+    fn king_attackers() {
+        // The queen and knight both hit the squares around the white king, far away they don't.
+        let attacked = king_safety("r5k1/5ppp/8/8/6nq/8/5PPP/R5K1 w - - 0 1");
+        let quiet = king_safety("rnq3k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1");
+        assert!(attacked.midgame < quiet.midgame - 50);
+        assert!(attacked.endgame < quiet.endgame);
+
+        // One piece on its own isn't an attack yet.
+        let lone_queen = king_safety("r5k1/5ppp/8/8/7q/8/5PPP/R5K1 w - - 0 1");
+        let far_queen = king_safety("r1q3k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1");
+        assert_eq!(lone_queen, far_queen);
+
+        // More attackers make the attack worse than the sum of its parts.
+        let heavier = king_safety("6k1/5ppp/8/8/6nq/5b2/5PPP/R5K1 w - - 0 1");
+        assert!(heavier.midgame < attacked.midgame);
+    }
+}
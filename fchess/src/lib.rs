@@ -2,16 +2,65 @@ pub mod chess_data;
 
 mod perft_tests;
 
+mod property_tests;
+
 pub mod structs;
 use structs::*;
 
 pub mod bitboard;
 
+#[cfg(not(feature = "kogge-stone"))]
 mod magics;
 
+pub mod sliders;
+
+mod slider_tests;
+
 mod constants;
 use constants::*;
 
 pub mod engine;
 
+mod engine_tests;
+
+pub mod evaluation;
+
+mod evaluation_tests;
+
+pub mod pawns;
+
+mod pawns_tests;
+
+pub mod king_safety;
+
+mod king_safety_tests;
+
+pub mod pieces;
+
+mod pieces_tests;
+
+pub mod score;
+
+mod score_tests;
+
+pub mod repetition;
+
+mod repetition_tests;
+
 pub mod move_generation;
+
+mod move_generation_tests;
+
+pub mod zobrist;
+
+pub mod transposition;
+
+mod transposition_tests;
+
+pub mod move_picker;
+
+mod move_picker_tests;
+
+pub mod time_manager;
+
+mod time_manager_tests;
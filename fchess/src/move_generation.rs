@@ -1,24 +1,20 @@
+use std::{
+    collections::BTreeMap,
+    ops::AddAssign,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    thread,
+};
+
 use crate::{
-    bitboard::BitBoard, magics, Board, BoardState, ChessMove, ChessTables, Color, LookupTable,
-    MoveType, Moves, Pieces, EMPTY_STRING, HUMAN_READBLE_SQAURES, MAX_MOVE_BUFFER,
+    bitboard::BitBoard, Board, BoardState, ChessMove, ChessTables, Color, LookupTable, MoveType,
+    Moves, Pieces, EMPTY_STRING, HUMAN_READBLE_SQAURES, MAX_MOVE_BUFFER,
 };
 
+use crate::evaluation::{phase_value, piece_square};
+use crate::sliders::{bishop_moves, rook_moves};
+use crate::zobrist::{castling_hash, en_passant_hash, ZOBRIST};
 use crate::{constants::*, LegalMoves};
 
-fn bishop_moves(position: u8, occupancy: BitBoard, tables: &ChessTables) -> BitBoard {
-    let movement_mask = tables.lookup_tables[LookupTable::BishopMoves as usize][position as usize]; // Short rook bitmask
-    let key = ((movement_mask & occupancy) * BitBoard(magics::MAGICS_BISHOP[position as usize])).0
-        >> magics::MAGIC_SHIFT_BISHOP;
-    BitBoard(magics::LOOKUP_BISHOP[position as usize][key as usize])
-}
-
-fn rook_moves(position: u8, occupancy: BitBoard, tables: &ChessTables) -> BitBoard {
-    let movement_mask = tables.lookup_tables[LookupTable::RookMoves as usize][position as usize]; // Short rook bitmask
-    let key = ((movement_mask & occupancy) * BitBoard(magics::MAGICS_ROOK[position as usize])).0
-        >> magics::MAGIC_SHIFT_ROOK;
-    BitBoard(magics::LOOKUP_ROOK[position as usize][key as usize])
-}
-
 impl Board {
     fn get_white_occupancy(&self) -> BitBoard {
         let bitboards: [BitBoard; 6] = self.bitboards[Color::White as usize];
@@ -47,12 +43,35 @@ impl Board {
         board_capturemask
     }
 
-    fn is_in_check(&self, tables: &ChessTables) -> bool {
+    pub fn is_in_check(&self, tables: &ChessTables) -> bool {
         let enemy_bitmask = self.get_full_capture_mask(self.turn.opposite(), tables);
 
         !(self.find_kind_bitboard(self.turn) & enemy_bitmask).is_empty()
     }
 
+    // Every enemy piece attacking the king of the side to move, found by looking outwards from the king.
+    fn get_checkers(&self, tables: &ChessTables) -> BitBoard {
+        let king_position = self.find_kind_bitboard(self.turn).0.trailing_zeros() as u8;
+        let enemy_bitboards = self.bitboards[self.turn.opposite() as usize];
+        let occupancy = self.get_white_occupancy() | self.get_black_occupancy();
+
+        let pawn_captures = match self.turn {
+            Color::White => LookupTable::WhitePawnCaptures,
+            Color::Black => LookupTable::BlackPawnCaptures,
+        };
+        let diagonal_sliders =
+            enemy_bitboards[Pieces::Bishop as usize] | enemy_bitboards[Pieces::Queen as usize];
+        let straight_sliders =
+            enemy_bitboards[Pieces::Rook as usize] | enemy_bitboards[Pieces::Queen as usize];
+
+        (tables.lookup_tables[LookupTable::KnightMoves as usize][king_position as usize]
+            & enemy_bitboards[Pieces::Knight as usize])
+            | (tables.lookup_tables[pawn_captures as usize][king_position as usize]
+                & enemy_bitboards[Pieces::Pawn as usize])
+            | (bishop_moves(king_position, occupancy, tables) & diagonal_sliders)
+            | (rook_moves(king_position, occupancy, tables) & straight_sliders)
+    }
+
     pub fn get_board_state(&self, tables: &ChessTables) -> BoardState {
         let legal_moves = self.get_all_legal_moves(tables);
         if legal_moves.length != 0 {
@@ -85,11 +104,84 @@ impl Board {
         }
     }
 
+    // The piece a move takes, Pieces::None for non-captures.
+    pub fn captured_piece(&self, chess_move: u16) -> Pieces {
+        let chess_move = ChessMove::unpack(chess_move);
+        if chess_move.move_type == MoveType::EnPassant {
+            return Pieces::Pawn; // The only capture that doesn't land on the captured piece.
+        }
+        let (piece, color) = self.find_piece(chess_move.destination);
+        if piece != Pieces::None && color != self.turn {
+            piece
+        } else {
+            Pieces::None
+        }
+    }
+
+    // Captures and promotions, the moves that change the material balance.
+    pub fn is_noisy(&self, chess_move: u16) -> bool {
+        let move_type = ChessMove::unpack(chess_move).move_type;
+        matches!(
+            move_type,
+            MoveType::QueenPromotion
+                | MoveType::RookPromotion
+                | MoveType::BishopPromotion
+                | MoveType::KnightPromotion
+        ) || self.captured_piece(chess_move) != Pieces::None
+    }
+
+    // Captures and pawn moves, no position from before one can come up again.
+    pub fn is_irreversible(&self, chess_move: u16) -> bool {
+        let (piece, _) = self.find_piece(ChessMove::unpack(chess_move).origin);
+        piece == Pieces::Pawn || self.captured_piece(chess_move) != Pieces::None
+    }
+
+    // Most valuable victim, least valuable attacker, https://www.chessprogramming.org/MVV-LVA
+    // Taking a queen with a pawn is sorted first, quiet moves score 0.
+    pub fn mvv_lva(&self, chess_move: u16) -> i32 {
+        const VALUES: [i32; 7] = [
+            0,
+            QUEEN_VALUE,
+            ROOK_VALUE,
+            BISHOP_VALUE,
+            KNIGHT_VALUE,
+            PAWN_VALUE,
+            0,
+        ]; // By Pieces, a king is never captured.
+
+        let victim = self.captured_piece(chess_move);
+        if victim == Pieces::None {
+            return 0;
+        }
+        let (attacker, _) = self.find_piece(ChessMove::unpack(chess_move).origin);
+        let attacker_value = match attacker {
+            Pieces::King => QUEEN_VALUE + PAWN_VALUE, // Only takes undefended pieces, but order it last anyway.
+            piece => VALUES[piece as usize],
+        };
+        VALUES[victim as usize] * 16 - attacker_value / 16
+    }
+
+    // Piece changes go through these so the hash and evaluation stay in sync with the bitboards.
     #[inline]
-    fn clear_square(&mut self, position: u8, color: Color) {
-        for bitboard in &mut self.bitboards[color as usize] {
-            bitboard.clear_bit(position);
+    fn place_piece(&mut self, color: Color, piece: Pieces, position: u8) {
+        self.bitboards[color as usize][piece as usize].set_bit(position);
+        self.hash ^= ZOBRIST.pieces[color as usize][piece as usize][position as usize];
+        if piece == Pieces::Pawn {
+            self.pawn_hash ^= ZOBRIST.pieces[color as usize][piece as usize][position as usize];
         }
+        self.piece_square += piece_square(color, piece, position);
+        self.phase += phase_value(piece);
+    }
+
+    #[inline]
+    fn remove_piece(&mut self, color: Color, piece: Pieces, position: u8) {
+        self.bitboards[color as usize][piece as usize].clear_bit(position);
+        self.hash ^= ZOBRIST.pieces[color as usize][piece as usize][position as usize];
+        if piece == Pieces::Pawn {
+            self.pawn_hash ^= ZOBRIST.pieces[color as usize][piece as usize][position as usize];
+        }
+        self.piece_square -= piece_square(color, piece, position);
+        self.phase -= phase_value(piece);
     }
 
     pub fn move_piece(&self, chess_move: u16) -> Board {
@@ -97,39 +189,33 @@ impl Board {
 
         let mut new_board = self.clone();
         let (piece_type, color) = new_board.find_piece(chess_move.origin);
-        let color_index = color as usize;
 
-        new_board.clear_square(chess_move.destination, new_board.turn.opposite());
-        new_board.bitboards[color_index][piece_type as usize].clear_bit(chess_move.origin);
+        // Take out the old castling and en passant state, the new state is hashed in at the end.
+        new_board.hash ^= castling_hash(self.castling_rights) ^ en_passant_hash(self.en_passant);
+
+        let (captured_piece, captured_color) = new_board.find_piece(chess_move.destination);
+        if captured_piece != Pieces::None {
+            new_board.remove_piece(captured_color, captured_piece, chess_move.destination);
+        }
+        new_board.remove_piece(color, piece_type, chess_move.origin);
+
+        let placed_piece = match chess_move.move_type {
+            MoveType::QueenPromotion => Pieces::Queen,
+            MoveType::RookPromotion => Pieces::Rook,
+            MoveType::BishopPromotion => Pieces::Bishop,
+            MoveType::KnightPromotion => Pieces::Knight,
+            _ => piece_type,
+        };
+        new_board.place_piece(color, placed_piece, chess_move.destination);
 
         match chess_move.move_type {
-            MoveType::QuietMove => new_board.bitboards[color_index][piece_type as usize]
-                .set_bit(chess_move.destination),
-            MoveType::DoublePawnPush => {
-                new_board.bitboards[color_index][piece_type as usize]
-                    .set_bit(chess_move.destination);
-            }
             MoveType::KingCastle => {
-                new_board.bitboards[color_index][piece_type as usize]
-                    .set_bit(chess_move.destination);
-
-                new_board.bitboards[color_index][Pieces::Rook as usize]
-                    .clear_bit(chess_move.destination - 1);
-                new_board.bitboards[color_index][Pieces::Rook as usize]
-                    .set_bit(chess_move.destination + 1);
+                new_board.remove_piece(color, Pieces::Rook, chess_move.destination - 1);
+                new_board.place_piece(color, Pieces::Rook, chess_move.destination + 1);
             }
             MoveType::QueenCastle => {
-                new_board.bitboards[color_index][piece_type as usize]
-                    .set_bit(chess_move.destination);
-
-                new_board.bitboards[color_index][Pieces::Rook as usize]
-                    .clear_bit(chess_move.destination + 2);
-                new_board.bitboards[color_index][Pieces::Rook as usize]
-                    .set_bit(chess_move.destination - 1);
-            }
-            MoveType::Capture => {
-                new_board.bitboards[color_index][piece_type as usize]
-                    .set_bit(chess_move.destination);
+                new_board.remove_piece(color, Pieces::Rook, chess_move.destination + 2);
+                new_board.place_piece(color, Pieces::Rook, chess_move.destination - 1);
             }
             MoveType::EnPassant => {
                 let direction: i32 = match new_board.turn {
@@ -137,43 +223,9 @@ impl Board {
                     Color::Black => 8,
                 };
                 let en_pasant_location = (chess_move.destination as i32 + direction) as u8;
-                new_board.bitboards[self.turn.opposite() as usize][Pieces::Pawn as usize]
-                    .clear_bit(en_pasant_location);
-                new_board.bitboards[color_index][piece_type as usize]
-                    .set_bit(chess_move.destination);
-            }
-            MoveType::QueenPromotion => {
-                match self.turn {
-                    Color::White => new_board.bitboards[color_index][Pieces::Queen as usize]
-                        .set_bit(chess_move.destination),
-                    Color::Black => new_board.bitboards[color_index][Pieces::Queen as usize]
-                        .set_bit(chess_move.destination),
-                };
-            }
-            MoveType::RookPromotion => {
-                match self.turn {
-                    Color::White => new_board.bitboards[color_index][Pieces::Rook as usize]
-                        .set_bit(chess_move.destination),
-                    Color::Black => new_board.bitboards[color_index][Pieces::Rook as usize]
-                        .set_bit(chess_move.destination),
-                };
-            }
-            MoveType::BishopPromotion => {
-                match self.turn {
-                    Color::White => new_board.bitboards[color_index][Pieces::Bishop as usize]
-                        .set_bit(chess_move.destination),
-                    Color::Black => new_board.bitboards[color_index][Pieces::Bishop as usize]
-                        .set_bit(chess_move.destination),
-                };
-            }
-            MoveType::KnightPromotion => {
-                match self.turn {
-                    Color::White => new_board.bitboards[color_index][Pieces::Knight as usize]
-                        .set_bit(chess_move.destination),
-                    Color::Black => new_board.bitboards[color_index][Pieces::Knight as usize]
-                        .set_bit(chess_move.destination),
-                };
+                new_board.remove_piece(self.turn.opposite(), Pieces::Pawn, en_pasant_location);
             }
+            _ => {}
         }
 
         if chess_move.move_type == MoveType::DoublePawnPush {
@@ -221,10 +273,30 @@ impl Board {
         }
 
         new_board.turn = new_board.other_color();
+        new_board.hash ^= castling_hash(new_board.castling_rights)
+            ^ en_passant_hash(new_board.en_passant)
+            ^ ZOBRIST.black_to_move;
+
+        new_board
+    }
 
+    // Passes the turn, for null-move pruning. Never legal in a real game, and the caller has to make sure it isn't in check.
+    pub fn make_null_move(&self) -> Board {
+        let mut new_board = self.clone();
+        new_board.hash ^= en_passant_hash(self.en_passant) ^ ZOBRIST.black_to_move;
+        new_board.en_passant = None;
+        new_board.turn = self.other_color();
         new_board
     }
 
+    // Anything besides the king and pawns. Without it zugzwang is common, so passing isn't a safe guess.
+    pub fn has_non_pawn_material(&self, color: Color) -> bool {
+        let pieces = &self.bitboards[color as usize];
+        [Pieces::Queen, Pieces::Rook, Pieces::Bishop, Pieces::Knight]
+            .iter()
+            .any(|&piece| !pieces[piece as usize].is_empty())
+    }
+
     pub fn get_pseudolegal_capture_mask(
         &self,
         position: u8,
@@ -242,9 +314,7 @@ impl Board {
 
         let movement_mask = match piece_type {
             Pieces::King => generate_king_bitmask(tables, friendly_occupancy, position),
-            Pieces::Pawn => {
-                generate_pawn_bitmask(color, tables, position, occupancy, enemy_occupancy)
-            }
+            Pieces::Pawn => generate_pawn_bitmask(color, tables, position, occupancy),
             Pieces::Knight => generate_knight_bitmask(tables, position, friendly_occupancy),
             Pieces::Rook => generate_rook_bitmask(position, occupancy, tables, friendly_occupancy),
             Pieces::Bishop => {
@@ -270,36 +340,21 @@ impl Board {
         if piece == Pieces::None {
             return Moves::default();
         }
-        if piece == Pieces::Pawn && self.en_passant.is_some() {
-            match color {
-                Color::White => {
-                    if !(tables.lookup_tables[LookupTable::WhitePawnCaptures as usize]
-                        [position as usize]
-                        & BitBoard(1 << (self.en_passant.unwrap())))
-                    .is_empty()
-                    {
-                        move_buffer.move_buffer[move_position] = ChessMove::pack(&ChessMove {
-                            origin: position,
-                            destination: self.en_passant.unwrap(),
-                            move_type: MoveType::EnPassant,
-                        });
-                        move_position += 1;
-                    }
-                }
-                Color::Black => {
-                    if !(tables.lookup_tables[LookupTable::BlackPawnCaptures as usize]
-                        [position as usize]
-                        & BitBoard(1 << (self.en_passant.unwrap())))
-                    .is_empty()
-                    {
-                        move_buffer.move_buffer[move_position] = ChessMove::pack(&ChessMove {
-                            origin: position,
-                            destination: self.en_passant.unwrap(),
-                            move_type: MoveType::EnPassant,
-                        });
-                        move_position += 1;
-                    }
-                }
+        if let (Pieces::Pawn, Some(en_passant)) = (piece, self.en_passant) {
+            let pawn_captures = match color {
+                Color::White => LookupTable::WhitePawnCaptures,
+                Color::Black => LookupTable::BlackPawnCaptures,
+            };
+            if !(tables.lookup_tables[pawn_captures as usize][position as usize]
+                & BitBoard(1 << en_passant))
+            .is_empty()
+            {
+                move_buffer.move_buffer[move_position] = ChessMove::pack(&ChessMove {
+                    origin: position,
+                    destination: en_passant,
+                    move_type: MoveType::EnPassant,
+                });
+                move_position += 1;
             }
         }
 
@@ -459,44 +514,31 @@ impl Board {
         move_buffer
     }
 
+    // Returns the move that was played, or None if no legal move matches.
     pub fn try_make_move(
         &mut self,
         position: u8,
         destination: u8,
         promotion_preference: char,
         tables: &ChessTables,
-    ) {
+    ) -> Option<u16> {
         let legal_moves = self.get_all_legal_moves(tables);
         for possible_move in 0..legal_moves.length {
             let parsed_move = ChessMove::unpack(legal_moves.move_buffer[possible_move as usize]);
             match parsed_move.move_type {
-                MoveType::QueenPromotion => {
-                    if promotion_preference != 'q' {
-                        continue;
-                    }
-                }
-                MoveType::RookPromotion => {
-                    if promotion_preference != 'r' {
-                        continue;
-                    }
-                }
-                MoveType::BishopPromotion => {
-                    if promotion_preference != 'b' {
-                        continue;
-                    }
-                }
-                MoveType::KnightPromotion => {
-                    if promotion_preference != 'k' {
-                        continue;
-                    }
-                }
-
+                MoveType::QueenPromotion if promotion_preference != 'q' => continue,
+                MoveType::RookPromotion if promotion_preference != 'r' => continue,
+                MoveType::BishopPromotion if promotion_preference != 'b' => continue,
+                MoveType::KnightPromotion if promotion_preference != 'n' => continue,
                 _ => {}
             }
             if parsed_move.origin == position && parsed_move.destination == destination {
-                *self = self.move_piece(legal_moves.move_buffer[possible_move as usize]);
+                let chess_move = legal_moves.move_buffer[possible_move as usize];
+                *self = self.move_piece(chess_move);
+                return Some(chess_move);
             }
         }
+        None
     }
 
     fn find_kind_bitboard(&self, color: Color) -> BitBoard {
@@ -595,8 +637,7 @@ impl Board {
         }
 
         // Now we have a buffer of all psuedolegal moves
-        for move_index in 0..array_position {
-            let chess_move = psuedolegal_move_buffer[move_index];
+        for &chess_move in psuedolegal_move_buffer.iter().take(array_position) {
             let temp_board = self.move_piece(chess_move);
 
             let mut friendly_occupancy = temp_board.get_white_occupancy();
@@ -627,9 +668,8 @@ impl Board {
                 continue;
             }
 
-            let pawn_inverse =
-                generate_pawn_bitmask(self.turn, tables, king_position, occupancy, enemy_occupancy)
-                    & enemy_bitboards[Pieces::Pawn as usize];
+            let pawn_inverse = generate_pawn_bitmask(self.turn, tables, king_position, occupancy)
+                & enemy_bitboards[Pieces::Pawn as usize];
             if !pawn_inverse.is_empty() {
                 continue;
             }
@@ -717,8 +757,8 @@ fn generate_pawn_bitmask(
     tables: &ChessTables,
     position: u8,
     occupancy: BitBoard,
-    enemy_occupancy: BitBoard,
 ) -> BitBoard {
+    // Captures are included whether there's something to take or not, so the mask also works as the attacked squares.
     let mut movement_mask = BitBoard(0);
     match color {
         Color::White => {
@@ -751,9 +791,8 @@ fn generate_pawn_bitmask(
                     [position as usize]
                     & !occupancy;
             }
-            movement_mask |= tables.lookup_tables[LookupTable::BlackPawnCaptures as usize]
-                [position as usize]
-                & enemy_occupancy;
+            movement_mask |=
+                tables.lookup_tables[LookupTable::BlackPawnCaptures as usize][position as usize]
         }
     }
     movement_mask
@@ -763,55 +802,247 @@ pub fn human_readable_position(position: u8) -> String {
     HUMAN_READBLE_SQAURES[position as usize].to_string()
 }
 
-fn perft_internal(board: Board, depth: u8, max_depth: u8, tables: &ChessTables) -> usize {
-    let all_legal_moves = board.get_all_legal_moves(tables);
-    if depth == max_depth {
-        return all_legal_moves.length as usize;
+// Lockless table of subtree sizes, https://www.chessprogramming.org/Perft#Hashing
+// Each entry stores the hash xored with its data, so an entry torn by two threads writing at once just looks like a miss.
+pub struct PerftHashTable {
+    entries: Vec<[AtomicU64; 2]>,
+}
+
+const PERFT_NODES_MASK: u64 = (1 << 56) - 1; // The depth goes in the top byte.
+
+impl PerftHashTable {
+    pub fn new(size_mb: usize) -> PerftHashTable {
+        let length = (size_mb * 1024 * 1024 / std::mem::size_of::<[AtomicU64; 2]>()).max(1);
+        PerftHashTable {
+            entries: (0..length)
+                .map(|_| [AtomicU64::new(0), AtomicU64::new(0)])
+                .collect(),
+        }
     }
 
-    match board.get_board_state(tables) {
-        BoardState::Checkmate => return all_legal_moves.length as usize,
-        BoardState::Stalemate => return all_legal_moves.length as usize,
-        BoardState::OnGoing => {}
+    fn probe(&self, hash: u64, depth: u8) -> Option<usize> {
+        let entry = &self.entries[(hash % self.entries.len() as u64) as usize];
+        let check = entry[0].load(Ordering::Relaxed);
+        let data = entry[1].load(Ordering::Relaxed);
+        if check ^ data == hash && (data >> 56) as u8 == depth {
+            Some((data & PERFT_NODES_MASK) as usize)
+        } else {
+            None
+        }
+    }
+
+    fn store(&self, hash: u64, depth: u8, nodes: usize) {
+        let entry = &self.entries[(hash % self.entries.len() as u64) as usize];
+        let data = nodes as u64 & PERFT_NODES_MASK | (depth as u64) << 56;
+        entry[0].store(hash ^ data, Ordering::Relaxed);
+        entry[1].store(data, Ordering::Relaxed);
+    }
+}
+
+fn perft_internal(
+    board: Board,
+    depth: u8,
+    tables: &ChessTables,
+    hash_table: Option<&PerftHashTable>,
+) -> usize {
+    if let Some(nodes) = hash_table.and_then(|hash_table| hash_table.probe(board.hash, depth)) {
+        return nodes;
+    }
+
+    let all_legal_moves = board.get_all_legal_moves(tables);
+    if depth == 1 {
+        return all_legal_moves.length as usize; // Bulk counting, no need to play the last ply.
     }
 
     let mut move_sum = 0;
 
     for possible_move in 0..all_legal_moves.length {
         let postmove = board.move_piece(all_legal_moves.move_buffer[possible_move as usize]);
-        move_sum += perft_internal(postmove, depth + 1, max_depth, tables);
+        move_sum += perft_internal(postmove, depth - 1, tables, hash_table);
     }
 
+    if let Some(hash_table) = hash_table {
+        hash_table.store(board.hash, depth, move_sum);
+    }
     move_sum
 }
 
-pub fn perft(board: Board, depth: u8, tables: &ChessTables) -> usize {
-    let mut sum = 0;
+#[derive(Debug, PartialEq)]
+pub struct PerftResult {
+    pub nodes: usize,
+    pub divide: BTreeMap<String, usize>, // Node count below each root move, keyed by the move in UCI notation.
+}
+
+// Root moves are handed out to the threads one at a time, so a slow subtree doesn't hold up the rest.
+fn perft_root(
+    board: Board,
+    depth: u8,
+    tables: &ChessTables,
+    threads: usize,
+    hash_table: Option<&PerftHashTable>,
+) -> PerftResult {
+    let mut result = PerftResult {
+        nodes: 0,
+        divide: BTreeMap::new(),
+    };
+    if depth == 0 {
+        result.nodes = 1;
+        return result;
+    }
+
+    let legal_moves = board.get_all_legal_moves(tables);
+    let next_move = AtomicUsize::new(0);
+
+    let count_moves = || {
+        let mut move_counts = Vec::new();
+        loop {
+            let possible_move = next_move.fetch_add(1, Ordering::Relaxed);
+            if possible_move >= legal_moves.length as usize {
+                break;
+            }
+            let packed_move = legal_moves.move_buffer[possible_move];
+            let move_count = if depth == 1 {
+                1
+            } else {
+                perft_internal(board.move_piece(packed_move), depth - 1, tables, hash_table)
+            };
+            move_counts.push((packed_move, move_count));
+        }
+        move_counts
+    };
+
+    let move_counts: Vec<(u16, usize)> = if threads <= 1 {
+        count_moves()
+    } else {
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads).map(|_| scope.spawn(count_moves)).collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        })
+    };
+
+    for (packed_move, move_count) in move_counts {
+        result.nodes += move_count;
+        result
+            .divide
+            .insert(ChessMove::unpack(packed_move).to_uci(), move_count);
+    }
+
+    result
+}
+
+pub fn perft(board: Board, depth: u8, tables: &ChessTables) -> PerftResult {
+    perft_root(board, depth, tables, 1, None)
+}
+
+pub fn perft_parallel(
+    board: Board,
+    depth: u8,
+    tables: &ChessTables,
+    threads: usize,
+) -> PerftResult {
+    perft_root(board, depth, tables, threads, None)
+}
+
+// Reuses subtree counts of transposed positions, the table can be shared between threads.
+pub fn perft_hashed(
+    board: Board,
+    depth: u8,
+    tables: &ChessTables,
+    threads: usize,
+    hash_table: &PerftHashTable,
+) -> PerftResult {
+    perft_root(board, depth, tables, threads, Some(hash_table))
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PerftStats {
+    pub nodes: usize,
+    pub captures: usize, // Includes en passant and capturing promotions.
+    pub en_passant: usize,
+    pub castles: usize,
+    pub promotions: usize,
+    pub checks: usize,
+    pub discovery_checks: usize, // The only piece giving check isn't the one that moved.
+    pub double_checks: usize,
+    pub checkmates: usize,
+}
+
+impl AddAssign for PerftStats {
+    fn add_assign(&mut self, other: PerftStats) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passant += other.en_passant;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+        self.discovery_checks += other.discovery_checks;
+        self.double_checks += other.double_checks;
+        self.checkmates += other.checkmates;
+    }
+}
+
+// Same as perft, but breaks the leaf nodes down by move category like the tables on https://www.chessprogramming.org/Perft_Results
+// This can't count in bulk since every leaf has to be played to see if it gives check, so it's a lot slower.
+pub fn perft_detailed(board: Board, depth: u8, tables: &ChessTables) -> PerftStats {
+    let mut stats = PerftStats::default();
+    if depth == 0 {
+        stats.nodes = 1;
+        return stats;
+    }
+
     let legal_moves = board.get_all_legal_moves(tables);
+    let enemy_occupancy = match board.turn {
+        Color::White => board.get_black_occupancy(),
+        Color::Black => board.get_white_occupancy(),
+    };
 
     for possible_move in 0..legal_moves.length {
-        let move_count = if depth == 1 {
-            1
-        } else {
-            perft_internal(
-                board.move_piece(legal_moves.move_buffer[possible_move as usize]),
-                1,
-                depth - 1,
-                tables,
-            )
-        };
-        sum += move_count;
-        let parsed = ChessMove::unpack(legal_moves.move_buffer[possible_move as usize]);
+        let packed_move = legal_moves.move_buffer[possible_move as usize];
+        let postmove = board.move_piece(packed_move);
+        if depth > 1 {
+            stats += perft_detailed(postmove, depth - 1, tables);
+            continue;
+        }
+
+        let chess_move = ChessMove::unpack(packed_move);
+        stats.nodes += 1;
+        match chess_move.move_type {
+            MoveType::EnPassant => {
+                stats.en_passant += 1;
+                stats.captures += 1;
+            }
+            MoveType::KingCastle | MoveType::QueenCastle => stats.castles += 1,
+            MoveType::QueenPromotion
+            | MoveType::RookPromotion
+            | MoveType::BishopPromotion
+            | MoveType::KnightPromotion => stats.promotions += 1,
+            _ => {}
+        }
+        if chess_move.move_type != MoveType::EnPassant
+            && enemy_occupancy.get_bit(chess_move.destination)
+        {
+            stats.captures += 1;
+        }
 
-        println!(
-            "{}{} {}",
-            human_readable_position(parsed.origin).to_lowercase(),
-            human_readable_position(parsed.destination).to_lowercase(),
-            move_count
-        );
+        let checkers = postmove.get_checkers(tables);
+        if checkers.is_empty() {
+            continue;
+        }
+        stats.checks += 1;
+        if checkers.popcnt() > 1 {
+            stats.double_checks += 1; // The published tables don't count these as discovery checks as well.
+        } else if !(checkers & !BitBoard(1 << chess_move.destination)).is_empty() {
+            stats.discovery_checks += 1;
+        }
+        if postmove.get_all_legal_moves(tables).length == 0 {
+            stats.checkmates += 1;
+        }
     }
 
-    sum
+    stats
 }
 
 fn generate_king_bitmask(
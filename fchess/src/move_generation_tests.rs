@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod tests {
+    use crate::{chess_data::fen_parser, ChessTables, Color, Pieces};
+
+    #[test]
+    fn try_make_move_promotions() {
+        // The last character of a UCI move like a7a8n picks the piece.
+        let tables = ChessTables::default();
+        let (a7, a8) = (55, 63);
+        for (promotion, piece) in [
+            ('q', Pieces::Queen),
+            ('r', Pieces::Rook),
+            ('b', Pieces::Bishop),
+            ('n', Pieces::Knight),
+        ] {
+            let mut board = fen_parser("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+            board.try_make_move(a7, a8, promotion, &tables);
+            assert!(
+                board.bitboards[Color::White as usize][piece as usize].get_bit(a8),
+                "a7a8{} didn't promote",
+                promotion
+            );
+            assert!(board.bitboards[Color::White as usize][Pieces::Pawn as usize].is_empty());
+        }
+    }
+}
@@ -0,0 +1,163 @@
+// Move ordering, https://www.chessprogramming.org/Move_Ordering
+// Alpha-beta cuts off sooner the earlier the best move is tried, so every move gets a score guessing how good it is.
+use crate::{constants::MAX_MOVE_BUFFER, Board, ChessMove, Color, LegalMoves, MoveType};
+
+pub const MAX_PLY: usize = 128;
+
+// Each kind of move gets its own range, so e.g. every capture comes before every killer.
+const HASH_MOVE_SCORE: i32 = 1 << 30;
+const CAPTURE_SCORE: i32 = 1 << 24; // Plus MVV-LVA
+const PROMOTION_SCORE: i32 = 1 << 23;
+const KILLER_SCORE: i32 = 1 << 22; // The first killer gets one more than the second.
+const COUNTER_MOVE_SCORE: i32 = 1 << 21;
+const UNDERPROMOTION_SCORE: i32 = -(1 << 21); // Almost never better than promoting to a queen.
+const MAX_HISTORY: i32 = 1 << 14; // Quiet moves are ordered by history, which stays within +-MAX_HISTORY.
+
+// What the search learns about quiet moves, kept per search thread.
+pub struct MoveOrdering {
+    killers: [[u16; 2]; MAX_PLY], // Quiet moves that caused a cutoff at the same ply, https://www.chessprogramming.org/Killer_Heuristic
+    counter_moves: [[u16; 64]; 64], // The quiet move that refuted the opponent's last move, by its origin and destination.
+    history: [[[i32; 64]; 64]; 2], // Butterfly history by color, origin and destination, https://www.chessprogramming.org/History_Heuristic
+}
+
+impl Default for MoveOrdering {
+    fn default() -> MoveOrdering {
+        MoveOrdering {
+            killers: [[0; 2]; MAX_PLY],
+            counter_moves: [[0; 64]; 64],
+            history: [[[0; 64]; 64]; 2],
+        }
+    }
+}
+
+impl MoveOrdering {
+    fn counter_move(&self, previous_move: u16) -> u16 {
+        if previous_move == 0 {
+            return 0;
+        }
+        let previous_move = ChessMove::unpack(previous_move);
+        self.counter_moves[previous_move.origin as usize][previous_move.destination as usize]
+    }
+
+    fn history_entry(&mut self, color: Color, chess_move: u16) -> &mut i32 {
+        let chess_move = ChessMove::unpack(chess_move);
+        &mut self.history[color as usize][chess_move.origin as usize]
+            [chess_move.destination as usize]
+    }
+
+    // Gravity, the bonus shrinks as the score gets closer to the limit so it never overflows and old results fade.
+    fn update_history(&mut self, color: Color, chess_move: u16, bonus: i32) {
+        let entry = self.history_entry(color, chess_move);
+        *entry += bonus - *entry * bonus.abs() / MAX_HISTORY;
+    }
+
+    // Called when a quiet move causes a beta cutoff, the quiet moves searched before it were a waste of time.
+    pub fn update_quiet_cutoff(
+        &mut self,
+        board: &Board,
+        ply: usize,
+        remaining_depth: u8,
+        chess_move: u16,
+        previous_move: u16,
+        quiets_tried: &[u16],
+    ) {
+        if ply < MAX_PLY && self.killers[ply][0] != chess_move {
+            self.killers[ply][1] = self.killers[ply][0];
+            self.killers[ply][0] = chess_move;
+        }
+
+        if previous_move != 0 {
+            let previous_move = ChessMove::unpack(previous_move);
+            self.counter_moves[previous_move.origin as usize][previous_move.destination as usize] =
+                chess_move;
+        }
+
+        let bonus = (remaining_depth as i32 * remaining_depth as i32).min(MAX_HISTORY);
+        self.update_history(board.turn, chess_move, bonus);
+        for &quiet in quiets_tried {
+            self.update_history(board.turn, quiet, -bonus);
+        }
+    }
+}
+
+pub struct MovePicker {
+    moves: [u16; MAX_MOVE_BUFFER],
+    scores: [i32; MAX_MOVE_BUFFER],
+    length: usize,
+    index: usize,
+}
+
+impl MovePicker {
+    // Scores every move up front, so the picker doesn't hold on to the board or the ordering tables.
+    pub fn new(
+        board: &Board,
+        legal_moves: &LegalMoves,
+        hash_move: u16,
+        ply: usize,
+        previous_move: u16,
+        ordering: &MoveOrdering,
+    ) -> MovePicker {
+        let mut picker = MovePicker {
+            moves: legal_moves.move_buffer,
+            scores: [0; MAX_MOVE_BUFFER],
+            length: legal_moves.length as usize,
+            index: 0,
+        };
+
+        let killers = ordering.killers[ply.min(MAX_PLY - 1)];
+        let counter_move = ordering.counter_move(previous_move);
+        for index in 0..picker.length {
+            let chess_move = picker.moves[index];
+            let move_type = ChessMove::unpack(chess_move).move_type;
+            let capture_score = board.mvv_lva(chess_move); // 0 for quiet moves
+
+            picker.scores[index] = if chess_move == hash_move {
+                HASH_MOVE_SCORE
+            } else if matches!(
+                move_type,
+                MoveType::KnightPromotion | MoveType::BishopPromotion | MoveType::RookPromotion
+            ) {
+                UNDERPROMOTION_SCORE
+            } else if capture_score != 0 {
+                CAPTURE_SCORE + capture_score
+            } else if move_type == MoveType::QueenPromotion {
+                PROMOTION_SCORE
+            } else if chess_move == killers[0] {
+                KILLER_SCORE + 1
+            } else if chess_move == killers[1] {
+                KILLER_SCORE
+            } else if chess_move == counter_move {
+                COUNTER_MOVE_SCORE
+            } else {
+                let chess_move = ChessMove::unpack(chess_move);
+                ordering.history[board.turn as usize][chess_move.origin as usize]
+                    [chess_move.destination as usize]
+            };
+        }
+
+        picker
+    }
+}
+
+impl Iterator for MovePicker {
+    type Item = u16;
+
+    // Selection sort, one step at a time. After a cutoff the rest of the moves never need sorting.
+    fn next(&mut self) -> Option<u16> {
+        if self.index >= self.length {
+            return None;
+        }
+
+        let mut best = self.index;
+        for index in self.index + 1..self.length {
+            if self.scores[index] > self.scores[best] {
+                best = index;
+            }
+        }
+        self.moves.swap(self.index, best);
+        self.scores.swap(self.index, best);
+
+        self.index += 1;
+        Some(self.moves[self.index - 1])
+    }
+}
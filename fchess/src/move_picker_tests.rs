@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chess_data::fen_parser,
+        move_picker::{MoveOrdering, MovePicker},
+        Board, ChessMove, ChessTables, STARTING_POSITION_FEN,
+    };
+
+    fn find_move(board: &Board, uci: &str, tables: &ChessTables) -> u16 {
+        let legal_moves = board.get_all_legal_moves(tables);
+        legal_moves.move_buffer[..legal_moves.length as usize]
+            .iter()
+            .copied()
+            .find(|&chess_move| ChessMove::unpack(chess_move).to_uci() == uci)
+            .unwrap()
+    }
+
+    fn picked(
+        board: &Board,
+        hash_move: u16,
+        ply: usize,
+        previous_move: u16,
+        ordering: &MoveOrdering,
+        tables: &ChessTables,
+    ) -> Vec<String> {
+        let legal_moves = board.get_all_legal_moves(tables);
+        let moves: Vec<String> =
+            MovePicker::new(board, &legal_moves, hash_move, ply, previous_move, ordering)
+                .map(|chess_move| ChessMove::unpack(chess_move).to_uci())
+                .collect();
+        assert_eq!(moves.len(), legal_moves.length as usize);
+        moves
+    }
+
+    #[test]
+    fn move_picker_noisy_moves() {
+        let tables = ChessTables::default();
+        let board = fen_parser("r3k3/1P6/3q4/8/3pN3/2P5/8/4K3 w - - 0 1");
+        let moves = picked(&board, 0, 0, 0, &MoveOrdering::default(), &tables);
+
+        // Captures by MVV-LVA, the capturing promotion to a queen counts as taking the rook.
+        assert_eq!(moves[..4], ["e4d6", "b7a8q", "c3d4", "b7b8q"]);
+        // Underpromotions go last.
+        let mut underpromotions = moves[moves.len() - 6..].to_vec();
+        underpromotions.sort();
+        assert_eq!(
+            underpromotions,
+            ["b7a8b", "b7a8n", "b7a8r", "b7b8b", "b7b8n", "b7b8r"]
+        );
+    }
+
+    #[test]
+    fn move_picker_quiet_moves() {
+        let tables = ChessTables::default();
+        let board = fen_parser(STARTING_POSITION_FEN);
+        let black_board = board.move_piece(find_move(&board, "e2e4", &tables));
+        let previous_move = find_move(&black_board, "e7e5", &tables);
+
+        let mut ordering = MoveOrdering::default();
+        let d2d4 = find_move(&board, "d2d4", &tables);
+        let a2a3 = find_move(&board, "a2a3", &tables);
+        let g2g3 = find_move(&board, "g2g3", &tables);
+        ordering.update_quiet_cutoff(&board, 0, 3, d2d4, previous_move, &[a2a3]);
+        ordering.update_quiet_cutoff(&board, 5, 2, g2g3, 0, &[]);
+
+        let hash_move = find_move(&board, "g1f3", &tables);
+        let moves = picked(&board, hash_move, 0, 0, &ordering, &tables);
+        assert_eq!(moves[..3], ["g1f3", "d2d4", "g2g3"]); // Hash move, killer, history
+        assert_eq!(moves.last().unwrap(), "a2a3"); // Failed to cause a cutoff
+
+        // No killers at this ply, but d2d4 refuted e7e5 before.
+        let moves = picked(&board, 0, 1, previous_move, &ordering, &tables);
+        assert_eq!(moves[..2], ["d2d4", "g2g3"]);
+    }
+}
@@ -0,0 +1,256 @@
+// Pawn structure, https://www.chessprogramming.org/Pawn_Structure
+// Each term is found for all of a side's pawns at once with fills, https://www.chessprogramming.org/Pawn_Fills
+// Pawns rarely move, so the result is cached by a hash of the pawns alone and the table almost always hits.
+use crate::bitboard::BitBoard;
+use crate::constants::*;
+use crate::evaluation::TaperedScore;
+use crate::{Board, Color, Pieces};
+
+const PAWN_TABLE_SIZE: usize = 1 << 14; // Entries, per search thread.
+
+// By rank from the pawn's own side, so the second rank is index 1.
+const PASSED_PAWN: [TaperedScore; 8] = [
+    TaperedScore::new(0, 0),
+    TaperedScore::new(5, 10),
+    TaperedScore::new(5, 15),
+    TaperedScore::new(10, 25),
+    TaperedScore::new(25, 50),
+    TaperedScore::new(50, 90),
+    TaperedScore::new(90, 150),
+    TaperedScore::new(0, 0),
+];
+// A piece standing in front of a passed pawn has to be moved or won before the pawn can go anywhere.
+const BLOCKED_PASSED_PAWN: [TaperedScore; 8] = [
+    TaperedScore::new(0, 0),
+    TaperedScore::new(3, 5),
+    TaperedScore::new(3, 8),
+    TaperedScore::new(5, 12),
+    TaperedScore::new(12, 25),
+    TaperedScore::new(25, 45),
+    TaperedScore::new(45, 75),
+    TaperedScore::new(0, 0),
+];
+const CANDIDATE_PASSER: [TaperedScore; 8] = [
+    TaperedScore::new(0, 0),
+    TaperedScore::new(2, 5),
+    TaperedScore::new(2, 5),
+    TaperedScore::new(5, 10),
+    TaperedScore::new(10, 20),
+    TaperedScore::new(20, 35),
+    TaperedScore::new(0, 0),
+    TaperedScore::new(0, 0),
+];
+const ISOLATED_PAWN: TaperedScore = TaperedScore::new(-10, -15);
+const DOUBLED_PAWN: TaperedScore = TaperedScore::new(-10, -25);
+const BACKWARD_PAWN: TaperedScore = TaperedScore::new(-8, -12);
+const SUPPORTED_PAWN: TaperedScore = TaperedScore::new(10, 8); // Defended by another pawn.
+const PHALANX_PAWN: TaperedScore = TaperedScore::new(6, 4); // Next to another pawn on the same rank.
+
+// Towards the A file, H1 is square 0 so that's a left shift.
+#[inline]
+fn shift_left(bitboard: BitBoard) -> BitBoard {
+    BitBoard(bitboard.0 << 1) & !BOARD_RIGHT
+}
+
+#[inline]
+fn shift_right(bitboard: BitBoard) -> BitBoard {
+    BitBoard(bitboard.0 >> 1) & !BOARD_LEFT
+}
+
+#[inline]
+pub fn forward_one(color: Color, bitboard: BitBoard) -> BitBoard {
+    match color {
+        Color::White => BitBoard(bitboard.0 << 8),
+        Color::Black => BitBoard(bitboard.0 >> 8),
+    }
+}
+
+#[inline]
+fn back_one(color: Color, bitboard: BitBoard) -> BitBoard {
+    forward_one(color.opposite(), bitboard)
+}
+
+// Every square from each bit to the far edge of the board, including the bit itself.
+#[inline]
+pub fn forward_fill(color: Color, bitboard: BitBoard) -> BitBoard {
+    let mut fill = bitboard.0;
+    match color {
+        Color::White => {
+            fill |= fill << 8;
+            fill |= fill << 16;
+            fill |= fill << 32;
+        }
+        Color::Black => {
+            fill |= fill >> 8;
+            fill |= fill >> 16;
+            fill |= fill >> 32;
+        }
+    }
+    BitBoard(fill)
+}
+
+#[inline]
+fn back_fill(color: Color, bitboard: BitBoard) -> BitBoard {
+    forward_fill(color.opposite(), bitboard)
+}
+
+#[inline]
+pub fn file_fill(bitboard: BitBoard) -> BitBoard {
+    forward_fill(Color::White, bitboard) | back_fill(Color::White, bitboard)
+}
+
+#[inline]
+fn adjacent(bitboard: BitBoard) -> BitBoard {
+    shift_left(bitboard) | shift_right(bitboard)
+}
+
+#[inline]
+pub fn pawn_attacks(color: Color, pawns: BitBoard) -> BitBoard {
+    adjacent(forward_one(color, pawns))
+}
+
+#[inline]
+pub fn relative_rank(color: Color, position: u8) -> usize {
+    match color {
+        Color::White => position as usize / 8,
+        Color::Black => 7 - position as usize / 8,
+    }
+}
+
+// Squares in front of the pawns on their own and the neighbouring files, where an enemy pawn could stop them.
+#[inline]
+fn passed_pawn_span(color: Color, pawns: BitBoard) -> BitBoard {
+    let front_span = forward_fill(color, forward_one(color, pawns));
+    front_span | adjacent(front_span)
+}
+
+// Pawns on a file with no enemy pawn in front of them, whether or not they're passed.
+#[inline]
+fn open_file_pawns(color: Color, pawns: BitBoard, enemy_pawns: BitBoard) -> BitBoard {
+    pawns & !back_fill(color, enemy_pawns)
+}
+
+struct PawnStructure {
+    score: TaperedScore,
+    passed: BitBoard,
+}
+
+fn evaluate_structure(color: Color, pawns: BitBoard, enemy_pawns: BitBoard) -> PawnStructure {
+    let attacks = pawn_attacks(color, pawns);
+    let enemy_attacks = pawn_attacks(color.opposite(), enemy_pawns);
+
+    // Only the front pawn of a doubled pair counts as passed, the one behind it is stuck.
+    let behind_own_pawn = pawns & back_fill(color, back_one(color, pawns));
+    let passed = pawns & !passed_pawn_span(color.opposite(), enemy_pawns) & !behind_own_pawn;
+    let isolated = pawns & !adjacent(file_fill(pawns));
+    let doubled = pawns & forward_fill(color, forward_one(color, pawns));
+    let supported = pawns & attacks;
+    let phalanx = pawns & adjacent(pawns);
+    // Can't safely move up, and no pawn on the files next to it can come up to defend the square in front.
+    let backward = back_one(
+        color,
+        forward_one(color, pawns) & enemy_attacks & !forward_fill(color, attacks),
+    ) & !isolated;
+
+    let mut score = ISOLATED_PAWN * isolated.popcnt() as i32
+        + DOUBLED_PAWN * doubled.popcnt() as i32
+        + BACKWARD_PAWN * backward.popcnt() as i32
+        + SUPPORTED_PAWN * supported.popcnt() as i32
+        + PHALANX_PAWN * phalanx.popcnt() as i32;
+
+    // A candidate has an open file ahead, and at least as many pawns to help it through as there are enemy pawns
+    // that could stop it. There are only ever a few, so they're counted one at a time.
+    let mut candidates = open_file_pawns(color, pawns, enemy_pawns) & !passed & !behind_own_pawn;
+    while !candidates.is_empty() {
+        let position = candidates.get_index_and_pop();
+        let pawn = BitBoard(1 << position);
+        let sentries = enemy_pawns & passed_pawn_span(color, pawn);
+        let helpers = pawns & adjacent(back_fill(color, pawn));
+        if helpers.popcnt() >= sentries.popcnt() {
+            score += CANDIDATE_PASSER[relative_rank(color, position)];
+        }
+    }
+
+    PawnStructure { score, passed }
+}
+
+#[derive(Clone, Copy)]
+struct PawnEntry {
+    key: u64,
+    score: TaperedScore,   // From white's side.
+    passed: [BitBoard; 2], // By color.
+}
+
+// Kept per search thread, so unlike the transposition table it doesn't need to be lockless.
+// An empty entry has key 0, which is also the key of a board without pawns, and its score is right for one too.
+pub struct PawnTable {
+    entries: Vec<PawnEntry>,
+}
+
+impl Default for PawnTable {
+    fn default() -> PawnTable {
+        PawnTable {
+            entries: vec![
+                PawnEntry {
+                    key: 0,
+                    score: TaperedScore::default(),
+                    passed: [BitBoard(0); 2],
+                };
+                PAWN_TABLE_SIZE
+            ],
+        }
+    }
+}
+
+impl PawnTable {
+    fn probe(&mut self, board: &Board) -> PawnEntry {
+        let index = board.pawn_hash as usize % PAWN_TABLE_SIZE;
+        if self.entries[index].key == board.pawn_hash {
+            return self.entries[index];
+        }
+
+        let white_pawns = board.bitboards[Color::White as usize][Pieces::Pawn as usize];
+        let black_pawns = board.bitboards[Color::Black as usize][Pieces::Pawn as usize];
+        let white = evaluate_structure(Color::White, white_pawns, black_pawns);
+        let black = evaluate_structure(Color::Black, black_pawns, white_pawns);
+        let entry = PawnEntry {
+            key: board.pawn_hash,
+            score: white.score - black.score,
+            passed: [white.passed, black.passed],
+        };
+        self.entries[index] = entry;
+        entry
+    }
+}
+
+// From white's side. Whether a passed pawn is blocked depends on the other pieces, so that part isn't cached.
+pub fn evaluate_pawns(board: &Board, pawn_table: &mut PawnTable) -> TaperedScore {
+    let entry = pawn_table.probe(board);
+
+    let mut occupancy = BitBoard(0);
+    for color in 0..2 {
+        for piece in 0..6 {
+            occupancy |= board.bitboards[color][piece];
+        }
+    }
+
+    let mut score = entry.score;
+    for color in [Color::White, Color::Black] {
+        let mut passed = entry.passed[color as usize];
+        let mut passed_score = TaperedScore::default();
+        while !passed.is_empty() {
+            let position = passed.get_index_and_pop();
+            let rank = relative_rank(color, position);
+            passed_score +=
+                match (forward_one(color, BitBoard(1 << position)) & occupancy).is_empty() {
+                    true => PASSED_PAWN[rank],
+                    false => BLOCKED_PASSED_PAWN[rank],
+                };
+        }
+        match color {
+            Color::White => score += passed_score,
+            Color::Black => score -= passed_score,
+        }
+    }
+    score
+}
@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chess_data::fen_parser,
+        evaluation::TaperedScore,
+        pawns::{evaluate_pawns, PawnTable},
+        STARTING_POSITION_FEN,
+    };
+
+    fn pawn_score(fen: &str) -> TaperedScore {
+        evaluate_pawns(&fen_parser(fen), &mut PawnTable::default())
+    }
+
+    #[test]
+    fn passed_pawns() {
+        assert_eq!(pawn_score(STARTING_POSITION_FEN), TaperedScore::default());
+
+        let fourth_rank = pawn_score("4k3/8/8/8/3P4/8/8/4K3 w - - 0 1");
+        let sixth_rank = pawn_score("4k3/8/3P4/8/8/8/8/4K3 w - - 0 1");
+        assert!(fourth_rank.endgame > 0);
+        assert!(sixth_rank.endgame > fourth_rank.endgame);
+
+        let blocked = pawn_score("4k3/3n4/3P4/8/8/8/8/4K3 w - - 0 1");
+        assert!(blocked.endgame < sixth_rank.endgame);
+
+        // The pawns on d4 and e6 stop each other, so neither is passed and the structure is even.
+        assert_eq!(
+            pawn_score("4k3/8/4p3/8/3P4/8/8/4K3 w - - 0 1"),
+            TaperedScore::default()
+        );
+        assert_eq!(pawn_score("4k3/8/8/8/8/3p4/8/4K3 w - - 0 1"), -sixth_rank);
+    }
+
+    #[test]
+    fn pawn_weaknesses() {
+        // None of these are passed, the black pawns cover every file.
+        let phalanx = pawn_score("4k3/ppp5/8/8/8/8/PP6/4K3 w - - 0 1");
+        let isolated = pawn_score("4k3/ppp5/8/8/8/8/P1P5/4K3 w - - 0 1");
+        let doubled = pawn_score("4k3/ppp5/8/8/8/1P6/1P6/4K3 w - - 0 1");
+        assert!(isolated.midgame < phalanx.midgame);
+        assert!(doubled.endgame < isolated.endgame);
+
+        // The pawn on d3 can't move up without being taken by the one on c5, and nothing can defend d4.
+        let backward = pawn_score("4k3/8/8/2p5/4P3/3P4/8/4K3 w - - 0 1");
+        let not_backward = pawn_score("4k3/8/2p5/8/4P3/3P4/8/4K3 w - - 0 1");
+        assert!(backward.midgame < not_backward.midgame);
+    }
+
+    #[test]
+    fn pawn_table() {
+        let mut pawn_table = PawnTable::default();
+        let board =
+            fen_parser("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let first = evaluate_pawns(&board, &mut pawn_table);
+        assert_eq!(evaluate_pawns(&board, &mut pawn_table), first);
+
+        // Moving a piece doesn't change the pawn key, only the blocked passed pawns can differ.
+        let moved =
+            fen_parser("r3k2r/p1ppqpb1/bn2pnp1/3P4/1p2P1N1/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        assert_eq!(moved.pawn_hash, board.pawn_hash);
+        assert_eq!(
+            evaluate_pawns(&moved, &mut pawn_table),
+            evaluate_pawns(&moved, &mut PawnTable::default())
+        );
+    }
+}
@@ -1,26 +1,46 @@
 #[cfg(test)]
 mod tests {
     use crate::{
-        chess_data::fen_parser, move_generation::perft, ChessTables, STARTING_POSITION_FEN,
+        chess_data::fen_parser,
+        move_generation::{
+            perft, perft_detailed, perft_hashed, perft_parallel, PerftHashTable, PerftStats,
+        },
+        ChessTables, STARTING_POSITION_FEN,
     };
 
     // https://www.chessprogramming.org/Perft_Results
 
-    /*
+    // Depth 5 is 193,690,690 nodes, still minutes in a debug build even hashed. Depth 4 has over 128k castles and
+    // goes through the threads and a small table, so entries get replaced while the workers share it.
     #[test]
-    fn perft_castling() {
+    fn perft_castling_deep() {
+        let tables = ChessTables::default();
+        let board = fen_parser("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - ");
+        let move_count = perft_hashed(board, 4, &tables, 4, &PerftHashTable::new(1)).nodes;
+        assert_eq!(move_count, 4_085_603);
+    }
+
+    #[test]
+    fn perft_parallel_matches() {
         let tables = ChessTables::default();
         let board = fen_parser("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - ");
-        let move_count = perft(board, 5, &tables);
-        assert_eq!(move_count, 193_690_690);
+        let expected = perft(board.clone(), 3, &tables);
+        assert_eq!(perft_parallel(board.clone(), 3, &tables, 4), expected);
+        assert_eq!(
+            perft_hashed(board.clone(), 3, &tables, 1, &PerftHashTable::new(1)),
+            expected
+        );
+        assert_eq!(
+            perft_hashed(board, 3, &tables, 4, &PerftHashTable::new(1)),
+            expected
+        );
     }
-    */
 
     #[test]
     fn perft_castling() {
         let tables = ChessTables::default();
         let board = fen_parser("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - ");
-        let move_count = perft(board, 4, &tables);
+        let move_count = perft(board, 4, &tables).nodes;
         assert_eq!(move_count, 4_085_603);
     }
 
@@ -28,15 +48,26 @@ mod tests {
     fn perft_base() {
         let tables = ChessTables::default();
         let board = fen_parser(STARTING_POSITION_FEN);
-        let move_count = perft(board, 5, &tables);
+        let move_count = perft(board, 5, &tables).nodes;
         assert_eq!(move_count, 4_865_609);
     }
 
+    #[test]
+    fn perft_divide() {
+        let tables = ChessTables::default();
+        let board = fen_parser(STARTING_POSITION_FEN);
+        let result = perft(board, 3, &tables);
+        assert_eq!(result.divide.len(), 20);
+        assert_eq!(result.divide["e2e4"], 600);
+        assert_eq!(result.divide["g1f3"], 440);
+        assert_eq!(result.divide.values().sum::<usize>(), result.nodes);
+    }
+
     #[test]
     fn perft_no_castle() {
         let tables = ChessTables::default();
         let board = fen_parser("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - ");
-        let move_count = perft(board, 6, &tables);
+        let move_count = perft(board, 6, &tables).nodes;
         assert_eq!(move_count, 11_030_083);
     }
 
@@ -44,7 +75,7 @@ mod tests {
     fn perft_strange() {
         let tables = ChessTables::default();
         let board = fen_parser("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1");
-        let move_count = perft(board, 5, &tables);
+        let move_count = perft(board, 5, &tables).nodes;
         assert_eq!(move_count, 15_833_292);
     }
 
@@ -52,7 +83,7 @@ mod tests {
     fn perft_promotion() {
         let tables = ChessTables::default();
         let board = fen_parser("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1");
-        let move_count = perft(board, 5, &tables);
+        let move_count = perft(board, 5, &tables).nodes;
         assert_eq!(move_count, 3_605_103);
     }
 
@@ -60,7 +91,85 @@ mod tests {
     fn perft_promotion_pinned() {
         let tables = ChessTables::default();
         let board = fen_parser("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8");
-        let move_count = perft(board, 4, &tables);
+        let move_count = perft(board, 4, &tables).nodes;
         assert_eq!(move_count, 2_103_487);
     }
+
+    #[test]
+    fn perft_detailed_base() {
+        let tables = ChessTables::default();
+        let board = fen_parser(STARTING_POSITION_FEN);
+        let stats = perft_detailed(board, 4, &tables);
+        assert_eq!(
+            stats,
+            PerftStats {
+                nodes: 197_281,
+                captures: 1_576,
+                en_passant: 0,
+                castles: 0,
+                promotions: 0,
+                checks: 469,
+                discovery_checks: 0,
+                double_checks: 0,
+                checkmates: 8,
+            }
+        );
+    }
+
+    #[test]
+    fn perft_detailed_castling() {
+        let tables = ChessTables::default();
+        let board = fen_parser("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - ");
+        let stats = perft_detailed(board, 3, &tables);
+        assert_eq!(
+            stats,
+            PerftStats {
+                nodes: 97_862,
+                captures: 17_102,
+                en_passant: 45,
+                castles: 3_162,
+                promotions: 0,
+                checks: 993,
+                discovery_checks: 0,
+                double_checks: 0,
+                checkmates: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn perft_detailed_no_castle() {
+        let tables = ChessTables::default();
+        let board = fen_parser("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - ");
+        let stats = perft_detailed(board, 5, &tables);
+        assert_eq!(
+            stats,
+            PerftStats {
+                nodes: 674_624,
+                captures: 52_051,
+                en_passant: 1_165,
+                castles: 0,
+                promotions: 0,
+                checks: 52_950,
+                discovery_checks: 1_292,
+                double_checks: 3,
+                checkmates: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn perft_detailed_strange() {
+        // This table doesn't list discovery or double checks.
+        let tables = ChessTables::default();
+        let board = fen_parser("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1");
+        let stats = perft_detailed(board, 4, &tables);
+        assert_eq!(stats.nodes, 422_333);
+        assert_eq!(stats.captures, 131_393);
+        assert_eq!(stats.en_passant, 0);
+        assert_eq!(stats.castles, 7_795);
+        assert_eq!(stats.promotions, 60_032);
+        assert_eq!(stats.checks, 15_492);
+        assert_eq!(stats.checkmates, 5);
+    }
 }
@@ -0,0 +1,239 @@
+// Positional terms for the pieces, https://www.chessprogramming.org/Evaluation_of_Pieces
+// Mobility counts the squares each piece attacks that an enemy pawn doesn't, scored per piece type since a rook
+// with few moves is much worse off than a knight with few moves. Attacks come from the same lookups as move generation.
+use crate::bitboard::BitBoard;
+use crate::constants::*;
+use crate::evaluation::TaperedScore;
+use crate::pawns::{file_fill, forward_fill, pawn_attacks, relative_rank};
+use crate::sliders::{bishop_moves, rook_moves};
+use crate::{Board, ChessTables, Color, LookupTable, Pieces};
+
+// By the number of safe squares attacked.
+const KNIGHT_MOBILITY: [TaperedScore; 9] = [
+    TaperedScore::new(-31, -40),
+    TaperedScore::new(-26, -28),
+    TaperedScore::new(-6, -15),
+    TaperedScore::new(-2, -7),
+    TaperedScore::new(2, 4),
+    TaperedScore::new(6, 8),
+    TaperedScore::new(11, 11),
+    TaperedScore::new(14, 14),
+    TaperedScore::new(16, 16),
+];
+const BISHOP_MOBILITY: [TaperedScore; 14] = [
+    TaperedScore::new(-24, -30),
+    TaperedScore::new(-10, -12),
+    TaperedScore::new(8, -2),
+    TaperedScore::new(13, 6),
+    TaperedScore::new(19, 12),
+    TaperedScore::new(25, 21),
+    TaperedScore::new(27, 27),
+    TaperedScore::new(31, 28),
+    TaperedScore::new(31, 32),
+    TaperedScore::new(34, 36),
+    TaperedScore::new(40, 39),
+    TaperedScore::new(40, 43),
+    TaperedScore::new(45, 44),
+    TaperedScore::new(49, 48),
+];
+const ROOK_MOBILITY: [TaperedScore; 15] = [
+    TaperedScore::new(-29, -38),
+    TaperedScore::new(-13, -9),
+    TaperedScore::new(-7, 14),
+    TaperedScore::new(-5, 27),
+    TaperedScore::new(-2, 34),
+    TaperedScore::new(-1, 41),
+    TaperedScore::new(4, 56),
+    TaperedScore::new(8, 59),
+    TaperedScore::new(15, 66),
+    TaperedScore::new(14, 71),
+    TaperedScore::new(16, 77),
+    TaperedScore::new(19, 82),
+    TaperedScore::new(23, 83),
+    TaperedScore::new(24, 84),
+    TaperedScore::new(29, 85),
+];
+const QUEEN_MOBILITY: [TaperedScore; 28] = [
+    TaperedScore::new(-20, -18),
+    TaperedScore::new(-10, -8),
+    TaperedScore::new(2, 4),
+    TaperedScore::new(2, 9),
+    TaperedScore::new(7, 17),
+    TaperedScore::new(11, 27),
+    TaperedScore::new(14, 30),
+    TaperedScore::new(20, 36),
+    TaperedScore::new(21, 40),
+    TaperedScore::new(24, 46),
+    TaperedScore::new(28, 47),
+    TaperedScore::new(30, 52),
+    TaperedScore::new(30, 56),
+    TaperedScore::new(33, 60),
+    TaperedScore::new(33, 61),
+    TaperedScore::new(35, 63),
+    TaperedScore::new(35, 66),
+    TaperedScore::new(36, 68),
+    TaperedScore::new(39, 70),
+    TaperedScore::new(44, 71),
+    TaperedScore::new(44, 74),
+    TaperedScore::new(49, 83),
+    TaperedScore::new(51, 85),
+    TaperedScore::new(51, 87),
+    TaperedScore::new(53, 92),
+    TaperedScore::new(54, 95),
+    TaperedScore::new(56, 103),
+    TaperedScore::new(58, 106),
+];
+
+const BISHOP_PAIR: TaperedScore = TaperedScore::new(25, 50);
+const ROOK_OPEN_FILE: TaperedScore = TaperedScore::new(20, 10); // No pawns on the file.
+const ROOK_SEMI_OPEN_FILE: TaperedScore = TaperedScore::new(10, 5); // Only enemy pawns on the file.
+const ROOK_ON_SEVENTH: TaperedScore = TaperedScore::new(10, 20);
+// A square in the enemy half that one of our pawns defends and no enemy pawn can ever attack.
+const KNIGHT_OUTPOST: TaperedScore = TaperedScore::new(25, 15);
+const BISHOP_OUTPOST: TaperedScore = TaperedScore::new(15, 8);
+const TRAPPED_ROOK: TaperedScore = TaperedScore::new(-40, -10);
+const TRAPPED_BISHOP: TaperedScore = TaperedScore::new(-80, -80);
+const TRAPPED_ROOK_MOBILITY: u32 = 3; // At most this many safe squares.
+
+// The fourth to sixth ranks from white's side, mirrored for black.
+const OUTPOST_RANKS: BitBoard = BitBoard(0x0000FFFFFF000000);
+const SEVENTH_RANK: BitBoard = BitBoard(0x00FF000000000000);
+const EIGHTH_RANK: BitBoard = BOARD_TOP;
+
+// White's side, black's squares are mirrored. A bishop that takes the pawn on a7 gets shut in by b6.
+const TRAPPED_BISHOP_SQUARES: [(u8, u8); 2] = [(55, 46), (48, 41)]; // (A7, B6) and (H7, G6)
+
+#[inline]
+fn relative_squares(color: Color, bitboard: BitBoard) -> BitBoard {
+    match color {
+        Color::White => bitboard,
+        Color::Black => BitBoard(bitboard.0.swap_bytes()),
+    }
+}
+
+#[inline]
+fn mobility(table: &[TaperedScore], attacks: BitBoard) -> TaperedScore {
+    table[(attacks.popcnt() as usize).min(table.len() - 1)]
+}
+
+fn side_pieces(
+    board: &Board,
+    color: Color,
+    occupancy: BitBoard,
+    tables: &ChessTables,
+) -> TaperedScore {
+    let pieces = &board.bitboards[color as usize];
+    let enemy = color.opposite();
+    let pawns = pieces[Pieces::Pawn as usize];
+    let enemy_pawns = board.bitboards[enemy as usize][Pieces::Pawn as usize];
+    let enemy_pawn_attacks = pawn_attacks(enemy, enemy_pawns);
+    let mobility_area = !(enemy_pawn_attacks | pawns | pieces[Pieces::King as usize]);
+    let outposts = relative_squares(color, OUTPOST_RANKS)
+        & pawn_attacks(color, pawns)
+        & !forward_fill(enemy, enemy_pawn_attacks);
+
+    let mut score = TaperedScore::default();
+
+    let mut knights = pieces[Pieces::Knight as usize];
+    while !knights.is_empty() {
+        let position = knights.get_index_and_pop();
+        let attacks = tables.lookup_tables[LookupTable::KnightMoves as usize][position as usize];
+        score += mobility(&KNIGHT_MOBILITY, attacks & mobility_area);
+        if outposts.get_bit(position) {
+            score += KNIGHT_OUTPOST;
+        }
+    }
+
+    let mut bishops = pieces[Pieces::Bishop as usize];
+    if bishops.popcnt() >= 2 {
+        score += BISHOP_PAIR;
+    }
+    while !bishops.is_empty() {
+        let position = bishops.get_index_and_pop();
+        let attacks = bishop_moves(position, occupancy, tables);
+        score += mobility(&BISHOP_MOBILITY, attacks & mobility_area);
+        if outposts.get_bit(position) {
+            score += BISHOP_OUTPOST;
+        }
+        for (bishop_square, pawn_square) in TRAPPED_BISHOP_SQUARES {
+            let (bishop_square, pawn_square) = match color {
+                Color::White => (bishop_square, pawn_square),
+                Color::Black => (bishop_square ^ 56, pawn_square ^ 56),
+            };
+            if position == bishop_square && enemy_pawns.get_bit(pawn_square) {
+                score += TRAPPED_BISHOP;
+            }
+        }
+    }
+
+    let own_files = file_fill(pawns);
+    let enemy_files = file_fill(enemy_pawns);
+    let king_position = pieces[Pieces::King as usize].0.trailing_zeros() as u8;
+    let enemy_king = board.bitboards[enemy as usize][Pieces::King as usize];
+    let mut rooks = pieces[Pieces::Rook as usize];
+    while !rooks.is_empty() {
+        let position = rooks.get_index_and_pop();
+        let attacks = rook_moves(position, occupancy, tables) & mobility_area;
+        score += mobility(&ROOK_MOBILITY, attacks);
+
+        if !own_files.get_bit(position) {
+            score += match enemy_files.get_bit(position) {
+                true => ROOK_SEMI_OPEN_FILE,
+                false => ROOK_OPEN_FILE,
+            };
+        }
+
+        // Only worth it when there's something there, pawns to eat or a king to cut off.
+        let seventh_rank = relative_squares(color, SEVENTH_RANK);
+        if seventh_rank.get_bit(position)
+            && (!(enemy_pawns & seventh_rank).is_empty()
+                || !(enemy_king & relative_squares(color, EIGHTH_RANK)).is_empty())
+        {
+            score += ROOK_ON_SEVENTH;
+        }
+
+        // Boxed in by its own king, which can't castle out of the way any more. Worse than a rook that's just passive.
+        let (rook_file, king_file) = (position % 8, king_position % 8);
+        let kingside = king_file < 4; // H1 is square 0, so the kingside files come first.
+        let can_castle = match (color, kingside) {
+            (Color::White, true) => board.castling_rights.white_kingside,
+            (Color::White, false) => board.castling_rights.white_queenside,
+            (Color::Black, true) => board.castling_rights.black_kingside,
+            (Color::Black, false) => board.castling_rights.black_queenside,
+        };
+        let boxed_in = match kingside {
+            true => rook_file < king_file,
+            false => rook_file > king_file,
+        };
+        if relative_rank(color, position) == 0
+            && relative_rank(color, king_position) == 0
+            && boxed_in
+            && !can_castle
+            && attacks.popcnt() <= TRAPPED_ROOK_MOBILITY
+        {
+            score += TRAPPED_ROOK;
+        }
+    }
+
+    let mut queens = pieces[Pieces::Queen as usize];
+    while !queens.is_empty() {
+        let position = queens.get_index_and_pop();
+        let attacks =
+            bishop_moves(position, occupancy, tables) | rook_moves(position, occupancy, tables);
+        score += mobility(&QUEEN_MOBILITY, attacks & mobility_area);
+    }
+
+    score
+}
+
+// From white's side.
+pub fn evaluate_pieces(board: &Board, tables: &ChessTables) -> TaperedScore {
+    let mut occupancy = BitBoard(0);
+    for color in 0..2 {
+        for piece in 0..6 {
+            occupancy |= board.bitboards[color][piece];
+        }
+    }
+    side_pieces(board, Color::White, occupancy, tables)
+        - side_pieces(board, Color::Black, occupancy, tables)
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        chess_data::fen_parser, evaluation::TaperedScore, pieces::evaluate_pieces, ChessTables,
+        STARTING_POSITION_FEN,
+    };
+
+    fn piece_score(fen: &str) -> TaperedScore {
+        evaluate_pieces(&fen_parser(fen), &ChessTables::default())
+    }
+
+    #[test]
+    fn piece_mobility() {
+        assert_eq!(piece_score(STARTING_POSITION_FEN), TaperedScore::default());
+
+        let center = piece_score("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+        let corner = piece_score("4k3/8/8/8/8/8/8/N3K3 w - - 0 1");
+        assert!(center.midgame > corner.midgame);
+
+        // The same knight, but with every square it could go to covered by black pawns.
+        let covered = piece_score("4k3/3p4/p5p1/8/2pN2p1/3p4/8/4K3 w - - 0 1");
+        assert!(covered.midgame < corner.midgame);
+
+        let free_rook = piece_score("4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+        let blocked_rook = piece_score("4k3/8/8/8/8/8/P7/RN2K3 w - - 0 1");
+        assert!(blocked_rook.endgame < free_rook.endgame);
+    }
+
+    #[test]
+    fn bishops_and_rooks() {
+        let pair = piece_score("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1");
+        let single = piece_score("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1");
+        assert!(pair.endgame > single.endgame * 2);
+
+        let open = piece_score("4k3/pp6/8/8/8/8/PP6/4K2R w - - 0 1");
+        let semi_open = piece_score("4k3/pp5p/8/8/8/8/PP6/4K2R w - - 0 1");
+        let closed = piece_score("4k3/pp5p/8/8/8/8/PP5P/4K2R w - - 0 1");
+        assert!(open.midgame > semi_open.midgame);
+        assert!(semi_open.midgame > closed.midgame);
+
+        // On the seventh it attacks the pawns and cuts off the king.
+        let seventh = piece_score("4k3/R4ppp/8/8/8/8/8/4K3 w - - 0 1");
+        let sixth = piece_score("4k3/5ppp/R7/8/8/8/8/4K3 w - - 0 1");
+        assert!(seventh.endgame > sixth.endgame);
+    }
+
+    #[test]
+    fn outposts_and_trapped_pieces() {
+        // Defended by the pawn on e4, and black has no pawn left that could chase it away.
+        let outpost = piece_score("4k3/p7/8/3N4/4P3/8/8/4K3 w - - 0 1");
+        let chased = piece_score("4k3/p1p5/8/3N4/4P3/8/8/4K3 w - - 0 1");
+        assert!(outpost.midgame > chased.midgame + 20);
+
+        let trapped_bishop = piece_score("4k3/B7/1p6/8/8/8/8/4K3 w - - 0 1");
+        let free_bishop = piece_score("4k3/B7/8/8/8/8/8/4K3 w - - 0 1");
+        assert!(trapped_bishop.midgame < free_bishop.midgame - 50);
+        // The same for black, mirrored.
+        assert_eq!(
+            piece_score("4k3/8/8/8/8/1P6/b7/4K3 b - - 0 1"),
+            -trapped_bishop
+        );
+
+        // The king walked to f1 instead of castling and now the rook on h1 can't get out.
+        let trapped_rook = piece_score("4k3/8/8/8/8/8/6PP/5K1R w - - 0 1");
+        let castled = piece_score("4k3/8/8/8/8/8/6PP/5RK1 w - - 0 1");
+        let can_castle = piece_score("4k3/8/8/8/8/8/6PP/4K2R w K - 0 1");
+        assert!(trapped_rook.midgame < castled.midgame - 30);
+        assert!(trapped_rook.midgame < can_castle.midgame - 30);
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use proptest::{prelude::*, sample::Index};
+
+    use crate::{
+        bitboard::BitBoard,
+        chess_data::{fen_parser, fen_writer},
+        evaluation::evaluate,
+        pawns::PawnTable,
+        Board, CastlingRights, ChessTables, Color, Pieces, STARTING_POSITION_FEN,
+    };
+
+    const START_POSITIONS: [&str; 4] = [
+        STARTING_POSITION_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ];
+
+    // Flips the board vertically and swaps the colors, so the position is the same but for the other side.
+    fn mirror(board: &Board) -> Board {
+        let mut mirrored = board.clone();
+        for piece in 0..6 {
+            mirrored.bitboards[Color::White as usize][piece] =
+                BitBoard(board.bitboards[Color::Black as usize][piece].0.swap_bytes());
+            mirrored.bitboards[Color::Black as usize][piece] =
+                BitBoard(board.bitboards[Color::White as usize][piece].0.swap_bytes());
+        }
+        mirrored.castling_rights = CastlingRights {
+            white_queenside: board.castling_rights.black_queenside,
+            white_kingside: board.castling_rights.black_kingside,
+            black_queenside: board.castling_rights.white_queenside,
+            black_kingside: board.castling_rights.white_kingside,
+        };
+        mirrored.en_passant = board.en_passant.map(|position| position ^ 56);
+        mirrored.turn = board.turn.opposite();
+        mirrored.hash = mirrored.compute_hash();
+        mirrored.pawn_hash = mirrored.compute_pawn_hash();
+        mirrored.piece_square = mirrored.compute_piece_square();
+        mirrored
+    }
+
+    fn check_invariants(board: &Board, tables: &ChessTables) -> Result<(), TestCaseError> {
+        let fen = fen_writer(board);
+        let parsed = fen_parser(&fen);
+        prop_assert!(
+            parsed.bitboards == board.bitboards,
+            "{} changed pieces",
+            fen
+        );
+        prop_assert_eq!(parsed.castling_rights, board.castling_rights, "{}", &fen);
+        prop_assert_eq!(parsed.en_passant, board.en_passant, "{}", &fen);
+        prop_assert_eq!(parsed.turn, board.turn, "{}", &fen);
+        prop_assert_eq!(fen_writer(&parsed), fen.clone());
+
+        let mut occupancy = BitBoard(0);
+        for color in 0..2 {
+            for piece in 0..6 {
+                let bitboard = board.bitboards[color][piece];
+                prop_assert!(
+                    (occupancy & bitboard).is_empty(),
+                    "{} has overlapping pieces",
+                    fen
+                );
+                occupancy |= bitboard;
+            }
+            prop_assert_eq!(board.bitboards[color][Pieces::King as usize].popcnt(), 1);
+        }
+
+        prop_assert_eq!(
+            board.hash,
+            board.compute_hash(),
+            "{} has a stale hash",
+            &fen
+        );
+        prop_assert_eq!(board.pawn_hash, board.compute_pawn_hash(), "{}", &fen);
+        prop_assert_eq!(
+            board.piece_square,
+            board.compute_piece_square(),
+            "{} has a stale piece-square score",
+            &fen
+        );
+        prop_assert_eq!(board.phase, board.compute_phase(), "{}", &fen);
+
+        if !board.is_in_check(tables) {
+            let null_move = board.make_null_move();
+            prop_assert_eq!(
+                null_move.hash,
+                null_move.compute_hash(),
+                "{} null move",
+                &fen
+            );
+        }
+
+        let legal_moves = board.get_all_legal_moves(tables);
+        for possible_move in 0..legal_moves.length {
+            let mut postmove = board.move_piece(legal_moves.move_buffer[possible_move as usize]);
+            postmove.turn = board.turn; // Ask if the side that just moved is in check.
+            prop_assert!(
+                !postmove.is_in_check(tables),
+                "{} leaves the king in check in {}",
+                crate::ChessMove::unpack(legal_moves.move_buffer[possible_move as usize]).to_uci(),
+                fen
+            );
+        }
+
+        let mirrored_moves = mirror(board).get_all_legal_moves(tables);
+        prop_assert_eq!(
+            mirrored_moves.length,
+            legal_moves.length,
+            "mirroring {} changed the move count",
+            fen
+        );
+        for color in [Color::White, Color::Black] {
+            prop_assert_eq!(
+                mirror(board)
+                    .get_full_capture_mask(color.opposite(), tables)
+                    .0,
+                board.get_full_capture_mask(color, tables).0.swap_bytes(),
+                "mirroring {} changed the squares {:?} attacks",
+                fen,
+                color
+            );
+        }
+        prop_assert_eq!(
+            evaluate(&mirror(board), tables, &mut PawnTable::default()),
+            evaluate(board, tables, &mut PawnTable::default()),
+            "mirroring {} changed the evaluation",
+            fen
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn attack_masks_symmetric() {
+        // Blocked pawns, so only the captures count. Both attack the empty squares diagonally in front of them.
+        let tables = ChessTables::default();
+        let board = fen_parser("4k3/8/8/4p3/4P3/8/8/4K3 w - - 0 1");
+        let white = board.get_full_capture_mask(Color::White, &tables);
+        let black = board.get_full_capture_mask(Color::Black, &tables);
+        assert_eq!(black.0, white.0.swap_bytes());
+        assert!(black.get_bit(28) && black.get_bit(26)); // d4 and f4
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(48))]
+
+        #[test]
+        fn random_games_keep_invariants(
+            start in prop::sample::select(&START_POSITIONS[..]),
+            moves in prop::collection::vec(any::<Index>(), 1..60),
+        ) {
+            let tables = ChessTables::default();
+            let mut board = fen_parser(start);
+
+            for chosen_move in moves {
+                check_invariants(&board, &tables)?;
+
+                let legal_moves = board.get_all_legal_moves(&tables);
+                if legal_moves.length == 0 {
+                    break; // Game over
+                }
+                let index = chosen_move.index(legal_moves.length as usize);
+                board = board.move_piece(legal_moves.move_buffer[index]);
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// Repetition detection, https://www.chessprogramming.org/Repetitions
+// The hashes of every position from the game and the search tree are kept on one stack. A repeat can only happen
+// after the last irreversible move, so the scan for one stops there.
+
+pub struct RepetitionStack {
+    hashes: Vec<u64>,         // Oldest first, the last one is the current position.
+    irreversible: Vec<usize>, // For each position, the index of the first one after the last irreversible move.
+    root: usize, // The index of the search's root, anything before it was played in the game.
+}
+
+impl RepetitionStack {
+    // The game history is every position before the root, oldest first. It only needs to go back to the last
+    // irreversible move, since nothing before it can be repeated.
+    pub fn new(game_history: &[u64], root_hash: u64) -> RepetitionStack {
+        let mut hashes = Vec::with_capacity(game_history.len() + 256);
+        hashes.extend_from_slice(game_history);
+        hashes.push(root_hash);
+        RepetitionStack {
+            irreversible: vec![0; hashes.len()],
+            root: hashes.len() - 1,
+            hashes,
+        }
+    }
+
+    // Captures and pawn moves can't be undone, a null move is treated the same so a repeat can't span it.
+    pub fn push(&mut self, hash: u64, irreversible: bool) {
+        let start = match irreversible {
+            true => self.hashes.len(),
+            false => *self.irreversible.last().unwrap(),
+        };
+        self.hashes.push(hash);
+        self.irreversible.push(start);
+    }
+
+    pub fn pop(&mut self) {
+        self.hashes.pop();
+        self.irreversible.pop();
+    }
+
+    // A position that already appeared in the search tree is a draw, the side that could avoid it would have.
+    // Repeats of positions from the game, or of the root itself, still need the full threefold.
+    pub fn is_draw(&self) -> bool {
+        let current = self.hashes.len() - 1;
+        let start = self.irreversible[current];
+        if current < start + 4 {
+            return false; // Going back and forth takes at least four plies.
+        }
+
+        let hash = self.hashes[current];
+        let mut game_repetitions = 0;
+        // Only positions with the same side to move can match, so every other one is skipped.
+        for index in (start..=current - 4).rev().step_by(2) {
+            if self.hashes[index] == hash {
+                if index > self.root {
+                    return true;
+                }
+                game_repetitions += 1;
+                if game_repetitions == 2 {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use crate::repetition::RepetitionStack;
+
+    #[test]
+    fn repetition_in_tree() {
+        // A, B, C, D, back to A. Seen once before, but inside the search, so it's already a draw.
+        let mut stack = RepetitionStack::new(&[], 1);
+        for hash in [2, 3, 4] {
+            stack.push(hash, false);
+            assert!(!stack.is_draw());
+        }
+        stack.push(1, false);
+        assert!(!stack.is_draw()); // That's the root, it needs a threefold.
+        stack.pop();
+
+        stack.push(5, false);
+        stack.push(2, false); // B was first seen after the root.
+        assert!(stack.is_draw());
+    }
+
+    #[test]
+    fn repetition_game_history() {
+        // The game went A, B, C, D, A, B, C, D and the root is A again, so A is on the board for the third time.
+        let history = [1, 2, 3, 4, 1, 2, 3, 4];
+        let stack = RepetitionStack::new(&history, 1);
+        assert!(stack.is_draw());
+
+        // Only twice, the game goes on.
+        let mut stack = RepetitionStack::new(&history[4..], 1);
+        assert!(!stack.is_draw());
+        // Until it comes up again in the search.
+        for hash in [2, 3, 4] {
+            stack.push(hash, false);
+        }
+        stack.push(1, false);
+        assert!(stack.is_draw());
+    }
+
+    #[test]
+    fn repetition_irreversible() {
+        let mut stack = RepetitionStack::new(&[], 1);
+        stack.push(2, false);
+        stack.push(3, false);
+        stack.push(4, true); // A capture, nothing before it can come up again.
+        stack.push(1, false);
+        assert!(!stack.is_draw()); // The same hash as the root, but the root was before the capture.
+        stack.push(5, false);
+        stack.push(6, false);
+        stack.push(4, false);
+        assert!(stack.is_draw());
+    }
+}
@@ -0,0 +1,101 @@
+// Search scores, either centipawns or a mate some number of plies from the root.
+// Mates are stored as the distance from MATE so faster mates score higher, and anything past MATE_THRESHOLD is a mate.
+use std::ops::{Add, Neg, Sub};
+
+const MATE: i32 = 999_999; // Large enough to overshadow any evaluation, small enough not to overflow.
+const MAX_MATE_PLY: i32 = 1000;
+const MATE_THRESHOLD: i32 = MATE - MAX_MATE_PLY;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Score(i32);
+
+impl Score {
+    pub const DRAW: Score = Score(0);
+    pub const INFINITE: Score = Score(MATE + 1); // Outside every real score, for the initial window.
+    pub const MAX_EVALUATION: Score = Score(MATE_THRESHOLD); // The highest score that isn't a mate.
+
+    pub const fn centipawns(centipawns: i32) -> Score {
+        Score(centipawns)
+    }
+
+    // The side to move mates in `ply` plies, counted the same way as the search counts them.
+    pub const fn mate(ply: usize) -> Score {
+        Score(MATE - ply as i32)
+    }
+
+    // The side to move is checkmated at `ply`.
+    pub const fn mated(ply: usize) -> Score {
+        Score(-MATE + ply as i32)
+    }
+
+    pub fn is_mate(self) -> bool {
+        self.0.abs() > MATE_THRESHOLD
+    }
+
+    // The ply the mate happens at, None for centipawn scores.
+    pub fn mate_ply(self) -> Option<i32> {
+        match self.is_mate() {
+            true => Some(MATE - self.0.abs()),
+            false => None,
+        }
+    }
+
+    // In full moves from the root, negative when the side to move is the one getting mated.
+    // The search numbers the root's children ply 0, so a mate found there is a mate in one.
+    pub fn mate_in_moves(self) -> Option<i32> {
+        let ply = self.mate_ply()?;
+        match self.0 > 0 {
+            true => Some((ply + 2) / 2),
+            false => Some(-(ply + 1) / 2),
+        }
+    }
+
+    pub fn centipawn_value(self) -> Option<i32> {
+        match self.is_mate() {
+            true => None,
+            false => Some(self.0),
+        }
+    }
+
+    // Mate scores count plies from the root, but the same position can be reached at different plies.
+    // So the transposition table stores them relative to the position itself, and converts back when probed.
+    pub fn to_table(self, ply: usize) -> i32 {
+        match self.is_mate() {
+            true => self.0 + self.0.signum() * ply as i32,
+            false => self.0,
+        }
+    }
+
+    pub fn from_table(value: i32, ply: usize) -> Score {
+        let score = Score(value);
+        match score.is_mate() {
+            true => Score((value - value.signum() * ply as i32).clamp(-MATE, MATE)),
+            false => score,
+        }
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score(-self.0)
+    }
+}
+
+// Margins and windows are plain centipawns.
+impl Add<i32> for Score {
+    type Output = Score;
+
+    fn add(self, centipawns: i32) -> Score {
+        Score(self.0 + centipawns)
+    }
+}
+
+impl Sub<i32> for Score {
+    type Output = Score;
+
+    fn sub(self, centipawns: i32) -> Score {
+        Score(self.0 - centipawns)
+    }
+}
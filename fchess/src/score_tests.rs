@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use crate::{engine::SearchScore, score::Score};
+
+    #[test]
+    fn score_mates() {
+        assert!(Score::mate(3).is_mate());
+        assert!(Score::mated(3).is_mate());
+        assert!(!Score::MAX_EVALUATION.is_mate());
+        assert!(Score::mate(3) > Score::mate(5)); // Faster mates are better.
+        assert!(Score::mated(5) > Score::mated(3));
+        assert!(-Score::INFINITE < Score::mated(0));
+        assert_eq!(-Score::mate(4), Score::mated(4));
+
+        assert_eq!(Score::mate(4).mate_ply(), Some(4));
+        assert_eq!(Score::mated(4).mate_ply(), Some(4));
+        assert_eq!(Score::centipawns(-300).mate_ply(), None);
+        assert_eq!(Score::centipawns(-300).centipawn_value(), Some(-300));
+        assert_eq!(Score::mate(0).centipawn_value(), None);
+    }
+
+    #[test]
+    fn score_mate_in_moves() {
+        // The root's children are ply 0, so a mate found there is the root move itself.
+        assert_eq!(Score::mate(0).mate_in_moves(), Some(1));
+        assert_eq!(Score::mate(2).mate_in_moves(), Some(2));
+        // Mated at ply 1, after the root move and the reply.
+        assert_eq!(Score::mated(1).mate_in_moves(), Some(-1));
+        assert_eq!(Score::mated(3).mate_in_moves(), Some(-2));
+
+        assert_eq!(SearchScore::from(Score::mate(2)), SearchScore::Mate(2));
+        assert_eq!(
+            SearchScore::from(Score::centipawns(35)),
+            SearchScore::Centipawns(35)
+        );
+        assert_eq!(SearchScore::from(Score::mated(3)).to_string(), "mate -2");
+    }
+
+    #[test]
+    fn score_table_roundtrip() {
+        for score in [Score::mate(7), Score::mated(7), Score::centipawns(123)] {
+            for ply in [0, 3, 20] {
+                assert_eq!(Score::from_table(score.to_table(ply), ply), score);
+            }
+        }
+        // Mate in 7 from the root, found 3 plies in, is mate in 4 from that position.
+        assert_eq!(
+            Score::from_table(Score::mate(7).to_table(3), 0),
+            Score::mate(4)
+        );
+        assert_eq!(
+            Score::from_table(Score::mated(7).to_table(3), 1),
+            Score::mated(5)
+        );
+    }
+}
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use crate::{
+        bitboard::BitBoard,
+        sliders::{KoggeStone, SliderBackend},
+        ChessTables,
+    };
+
+    // The brute force raycaster magic_generator builds its tables from, used as the reference.
+    use raycast::{generate_bishop_moves, generate_rook_moves, raycast_calculate, RaycastTables};
+
+    fn cross_check(backend: &impl SliderBackend) {
+        let tables = ChessTables::default();
+        let raycast_tables = RaycastTables::default();
+        let (bishop_moves, rook_moves) = (generate_bishop_moves(), generate_rook_moves());
+        let mut rng = ChaCha8Rng::seed_from_u64(123);
+
+        for _ in 0..20_000 {
+            let position: u8 = rng.gen_range(0..64);
+            // Vary the density, sparse boards have long rays and dense boards have short ones.
+            let occupancy = match rng.gen_range(0..3) {
+                0 => rng.gen::<u64>(),
+                1 => rng.gen::<u64>() & rng.gen::<u64>(),
+                _ => rng.gen::<u64>() & rng.gen::<u64>() & rng.gen::<u64>(),
+            };
+
+            assert_eq!(
+                backend
+                    .bishop_attacks(position, BitBoard(occupancy), &tables)
+                    .0,
+                bishop_moves[position as usize]
+                    & raycast_calculate(position, occupancy, &raycast_tables),
+                "bishop on {} with occupancy {:#x}",
+                position,
+                occupancy
+            );
+            assert_eq!(
+                backend
+                    .rook_attacks(position, BitBoard(occupancy), &tables)
+                    .0,
+                rook_moves[position as usize]
+                    & raycast_calculate(position, occupancy, &raycast_tables),
+                "rook on {} with occupancy {:#x}",
+                position,
+                occupancy
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "kogge-stone"))]
+    fn sliders_magic() {
+        cross_check(&crate::sliders::Magic);
+    }
+
+    #[test]
+    #[cfg(all(target_arch = "x86_64", not(feature = "kogge-stone")))]
+    fn sliders_pext() {
+        if let Some(pext) = crate::sliders::Pext::detect() {
+            cross_check(&pext);
+        }
+    }
+
+    #[test]
+    fn sliders_kogge_stone() {
+        cross_check(&KoggeStone);
+    }
+
+    #[test]
+    fn sliders_active_backend() {
+        cross_check(&crate::sliders::active_backend());
+    }
+}
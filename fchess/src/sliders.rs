@@ -0,0 +1,285 @@
+// Sliding piece attack generation.
+// There are a few ways to do this, the fastest depends on the CPU and how much memory we're allowed to use.
+//  * Magic: magic bitboards from magic_generator, the default.
+//  * Pext: BMI2 parallel bit extract, only used when the CPU supports it (checked at runtime).
+//  * KoggeStone: table-free parallel prefix fills, enabled with the `kogge-stone` feature for memory-constrained builds.
+use std::sync::OnceLock;
+
+use crate::{bitboard::BitBoard, ChessTables};
+
+#[cfg(not(feature = "kogge-stone"))]
+use crate::{chess_data::generate_data, magics, LookupTable};
+
+pub trait SliderBackend {
+    fn bishop_attacks(&self, position: u8, occupancy: BitBoard, tables: &ChessTables) -> BitBoard;
+    fn rook_attacks(&self, position: u8, occupancy: BitBoard, tables: &ChessTables) -> BitBoard;
+}
+
+#[cfg(not(feature = "kogge-stone"))]
+#[derive(Clone, Copy)]
+pub struct Magic;
+
+#[cfg(not(feature = "kogge-stone"))]
+impl SliderBackend for Magic {
+    #[inline]
+    fn bishop_attacks(&self, position: u8, occupancy: BitBoard, tables: &ChessTables) -> BitBoard {
+        let movement_mask =
+            tables.lookup_tables[LookupTable::BishopMoves as usize][position as usize]; // Short bishop bitmask
+        let key = ((movement_mask & occupancy)
+            * BitBoard(magics::MAGICS_BISHOP[position as usize]))
+        .0 >> magics::MAGIC_SHIFTS_BISHOP[position as usize];
+        BitBoard(
+            magics::LOOKUP_BISHOP
+                [magics::MAGIC_OFFSETS_BISHOP[position as usize] as usize + key as usize],
+        )
+    }
+
+    #[inline]
+    fn rook_attacks(&self, position: u8, occupancy: BitBoard, tables: &ChessTables) -> BitBoard {
+        let movement_mask =
+            tables.lookup_tables[LookupTable::RookMoves as usize][position as usize]; // Short rook bitmask
+        let key = ((movement_mask & occupancy) * BitBoard(magics::MAGICS_ROOK[position as usize]))
+            .0
+            >> magics::MAGIC_SHIFTS_ROOK[position as usize];
+        BitBoard(
+            magics::LOOKUP_ROOK
+                [magics::MAGIC_OFFSETS_ROOK[position as usize] as usize + key as usize],
+        )
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct KoggeStone;
+
+// Masks that stop a shifted bitboard from wrapping onto the next rank, bit 0 is H1 so shifting up by one moves towards the A file.
+const NOT_H_FILE: u64 = !0x101010101010101;
+const NOT_A_FILE: u64 = !0x8080808080808080;
+
+// (shift, wrap mask) for every direction a piece can slide in.
+const BISHOP_DIRECTIONS: [(i32, u64); 4] = [
+    (9, NOT_H_FILE),
+    (7, NOT_A_FILE),
+    (-7, NOT_H_FILE),
+    (-9, NOT_A_FILE),
+];
+const ROOK_DIRECTIONS: [(i32, u64); 4] = [
+    (8, u64::MAX),
+    (-8, u64::MAX),
+    (1, NOT_H_FILE),
+    (-1, NOT_A_FILE),
+];
+
+#[inline]
+fn shift(bitboard: u64, amount: i32) -> u64 {
+    if amount > 0 {
+        bitboard << amount
+    } else {
+        bitboard >> -amount
+    }
+}
+
+#[inline]
+fn occluded_fill(mut generator: u64, mut propagator: u64, amount: i32, wrap: u64) -> u64 {
+    // https://www.chessprogramming.org/Kogge-Stone_Algorithm
+    propagator &= wrap;
+    generator |= propagator & shift(generator, amount);
+    propagator &= shift(propagator, amount);
+    generator |= propagator & shift(generator, 2 * amount);
+    propagator &= shift(propagator, 2 * amount);
+    generator |= propagator & shift(generator, 4 * amount);
+    shift(generator, amount) & wrap // Shift once more so the fill becomes the attacks, blocker included.
+}
+
+impl KoggeStone {
+    #[inline]
+    fn fill(position: u8, occupancy: BitBoard, directions: [(i32, u64); 4]) -> BitBoard {
+        let generator = 1 << position;
+        let empty = !occupancy.0;
+        let mut attacks = 0;
+        for (amount, wrap) in directions {
+            attacks |= occluded_fill(generator, empty, amount, wrap);
+        }
+        BitBoard(attacks)
+    }
+}
+
+impl SliderBackend for KoggeStone {
+    #[inline]
+    fn bishop_attacks(&self, position: u8, occupancy: BitBoard, _: &ChessTables) -> BitBoard {
+        KoggeStone::fill(position, occupancy, BISHOP_DIRECTIONS)
+    }
+
+    #[inline]
+    fn rook_attacks(&self, position: u8, occupancy: BitBoard, _: &ChessTables) -> BitBoard {
+        KoggeStone::fill(position, occupancy, ROOK_DIRECTIONS)
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", not(feature = "kogge-stone")))]
+pub struct PextTables {
+    bishop_masks: [u64; 64],
+    bishop_offsets: [usize; 64],
+    rook_masks: [u64; 64],
+    rook_offsets: [usize; 64],
+    attacks: Vec<u64>,
+}
+
+#[cfg(all(target_arch = "x86_64", not(feature = "kogge-stone")))]
+impl PextTables {
+    #[allow(clippy::needless_range_loop)]
+    fn new() -> PextTables {
+        fn deposit(mut index: u64, mut mask: u64) -> u64 {
+            // Software PDEP, the table is only built once so this doesn't need to be fast.
+            let mut result = 0;
+            while mask != 0 {
+                let bit = mask & mask.wrapping_neg();
+                if index & 1 != 0 {
+                    result |= bit;
+                }
+                index >>= 1;
+                mask ^= bit;
+            }
+            result
+        }
+
+        let lookup_tables = generate_data();
+        let mut tables = PextTables {
+            bishop_masks: [0; 64],
+            bishop_offsets: [0; 64],
+            rook_masks: [0; 64],
+            rook_offsets: [0; 64],
+            attacks: Vec::new(),
+        };
+
+        for position in 0..64 {
+            let bishop_mask = lookup_tables[LookupTable::BishopMoves as usize][position].0;
+            tables.bishop_masks[position] = bishop_mask;
+            tables.bishop_offsets[position] = tables.attacks.len();
+            for index in 0..1 << bishop_mask.count_ones() {
+                let occupancy = BitBoard(deposit(index, bishop_mask));
+                tables
+                    .attacks
+                    .push(KoggeStone::fill(position as u8, occupancy, BISHOP_DIRECTIONS).0);
+            }
+
+            let rook_mask = lookup_tables[LookupTable::RookMoves as usize][position].0;
+            tables.rook_masks[position] = rook_mask;
+            tables.rook_offsets[position] = tables.attacks.len();
+            for index in 0..1 << rook_mask.count_ones() {
+                let occupancy = BitBoard(deposit(index, rook_mask));
+                tables
+                    .attacks
+                    .push(KoggeStone::fill(position as u8, occupancy, ROOK_DIRECTIONS).0);
+            }
+        }
+
+        tables
+    }
+}
+
+// Can only be obtained through Pext::detect, which guarantees the CPU supports BMI2.
+#[cfg(all(target_arch = "x86_64", not(feature = "kogge-stone")))]
+#[derive(Clone, Copy)]
+pub struct Pext(&'static PextTables);
+
+#[cfg(all(target_arch = "x86_64", not(feature = "kogge-stone")))]
+impl Pext {
+    pub fn detect() -> Option<Pext> {
+        static PEXT_TABLES: OnceLock<PextTables> = OnceLock::new();
+        if std::arch::is_x86_feature_detected!("bmi2") {
+            Some(Pext(PEXT_TABLES.get_or_init(PextTables::new)))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn extract(occupancy: u64, mask: u64) -> u64 {
+        #[target_feature(enable = "bmi2")]
+        unsafe fn pext(occupancy: u64, mask: u64) -> u64 {
+            std::arch::x86_64::_pext_u64(occupancy, mask)
+        }
+        unsafe { pext(occupancy, mask) } // Safe since a Pext only exists when BMI2 is available.
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", not(feature = "kogge-stone")))]
+impl SliderBackend for Pext {
+    #[inline]
+    fn bishop_attacks(&self, position: u8, occupancy: BitBoard, _: &ChessTables) -> BitBoard {
+        let tables = self.0;
+        let key = Pext::extract(occupancy.0, tables.bishop_masks[position as usize]) as usize;
+        BitBoard(tables.attacks[tables.bishop_offsets[position as usize] + key])
+    }
+
+    #[inline]
+    fn rook_attacks(&self, position: u8, occupancy: BitBoard, _: &ChessTables) -> BitBoard {
+        let tables = self.0;
+        let key = Pext::extract(occupancy.0, tables.rook_masks[position as usize]) as usize;
+        BitBoard(tables.attacks[tables.rook_offsets[position as usize] + key])
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Backend {
+    #[cfg(not(feature = "kogge-stone"))]
+    Magic(Magic),
+    #[cfg(all(target_arch = "x86_64", not(feature = "kogge-stone")))]
+    Pext(Pext),
+    KoggeStone(KoggeStone),
+}
+
+impl SliderBackend for Backend {
+    #[inline]
+    fn bishop_attacks(&self, position: u8, occupancy: BitBoard, tables: &ChessTables) -> BitBoard {
+        match self {
+            #[cfg(not(feature = "kogge-stone"))]
+            Backend::Magic(backend) => backend.bishop_attacks(position, occupancy, tables),
+            #[cfg(all(target_arch = "x86_64", not(feature = "kogge-stone")))]
+            Backend::Pext(backend) => backend.bishop_attacks(position, occupancy, tables),
+            Backend::KoggeStone(backend) => backend.bishop_attacks(position, occupancy, tables),
+        }
+    }
+
+    #[inline]
+    fn rook_attacks(&self, position: u8, occupancy: BitBoard, tables: &ChessTables) -> BitBoard {
+        match self {
+            #[cfg(not(feature = "kogge-stone"))]
+            Backend::Magic(backend) => backend.rook_attacks(position, occupancy, tables),
+            #[cfg(all(target_arch = "x86_64", not(feature = "kogge-stone")))]
+            Backend::Pext(backend) => backend.rook_attacks(position, occupancy, tables),
+            Backend::KoggeStone(backend) => backend.rook_attacks(position, occupancy, tables),
+        }
+    }
+}
+
+#[cfg(feature = "kogge-stone")]
+fn detect_backend() -> Backend {
+    Backend::KoggeStone(KoggeStone)
+}
+
+#[cfg(not(feature = "kogge-stone"))]
+fn detect_backend() -> Backend {
+    #[cfg(target_arch = "x86_64")]
+    if let Some(pext) = Pext::detect() {
+        return Backend::Pext(pext);
+    }
+
+    Backend::Magic(Magic)
+}
+
+// The backend is picked once, the first time a slider is generated.
+pub fn active_backend() -> Backend {
+    static BACKEND: OnceLock<Backend> = OnceLock::new();
+    *BACKEND.get_or_init(detect_backend)
+}
+
+#[inline]
+pub fn bishop_moves(position: u8, occupancy: BitBoard, tables: &ChessTables) -> BitBoard {
+    active_backend().bishop_attacks(position, occupancy, tables)
+}
+
+#[inline]
+pub fn rook_moves(position: u8, occupancy: BitBoard, tables: &ChessTables) -> BitBoard {
+    active_backend().rook_attacks(position, occupancy, tables)
+}
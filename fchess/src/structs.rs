@@ -1,4 +1,6 @@
-use crate::{bitboard::BitBoard, chess_data::generate_data, constants::*};
+use crate::{
+    bitboard::BitBoard, chess_data::generate_data, constants::*, evaluation::TaperedScore,
+};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Color {
@@ -46,7 +48,7 @@ impl MoveType {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct ChessMove {
     pub origin: u8,
     pub destination: u8,
@@ -77,6 +79,23 @@ impl ChessMove {
             move_type,
         }
     }
+
+    // Long algebraic notation as used by UCI, e.g. e2e4 or a7a8q.
+    pub fn to_uci(&self) -> String {
+        let suffix = match self.move_type {
+            MoveType::QueenPromotion => "q",
+            MoveType::RookPromotion => "r",
+            MoveType::BishopPromotion => "b",
+            MoveType::KnightPromotion => "n",
+            _ => "",
+        };
+        format!(
+            "{}{}{}",
+            HUMAN_READBLE_SQAURES[self.origin as usize].to_lowercase(),
+            HUMAN_READBLE_SQAURES[self.destination as usize].to_lowercase(),
+            suffix
+        )
+    }
 }
 
 pub static STARTING_POSITION: [[BitBoard; 6]; 2] = [
@@ -230,7 +249,7 @@ pub enum BoardState {
     OnGoing,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct CastlingRights {
     pub white_queenside: bool,
     pub white_kingside: bool,
@@ -267,15 +286,28 @@ pub struct Board {
     pub castling_rights: CastlingRights,
     pub en_passant: Option<u8>, // Denotes the position of where the en passant square can be captured
     pub turn: Color,
+    pub hash: u64,                  // Zobrist hash, kept up to date by move_piece.
+    pub pawn_hash: u64,             // Only the pawns, for the pawn structure cache.
+    pub piece_square: TaperedScore, // Material and piece-square score from white's side, also kept up to date.
+    pub phase: i32,
 }
 
 impl Default for Board {
     fn default() -> Self {
-        Board {
+        let mut board = Board {
             bitboards: STARTING_POSITION,
             castling_rights: CastlingRights::default(),
             en_passant: None,
             turn: Color::White,
-        }
+            hash: 0,
+            pawn_hash: 0,
+            piece_square: TaperedScore::default(),
+            phase: 0,
+        };
+        board.hash = board.compute_hash();
+        board.pawn_hash = board.compute_pawn_hash();
+        board.piece_square = board.compute_piece_square();
+        board.phase = board.compute_phase();
+        board
     }
 }
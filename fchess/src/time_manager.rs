@@ -0,0 +1,104 @@
+// Decides how long a search may run, https://www.chessprogramming.org/Time_Management
+// There are two limits, the soft limit is checked between iterations and the hard limit while searching.
+use std::time::{Duration, Instant};
+
+use crate::Color;
+
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50); // Time lost talking to the GUI.
+const DEFAULT_MOVES_TO_GO: u32 = 30; // Assume the game lasts this many more moves when the GUI doesn't say.
+
+// The clock as sent by the GUI in "go", anything not sent is None.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TimeControl {
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<u32>,
+    pub movetime: Option<Duration>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TimeManager {
+    start: Instant,
+    soft_limit: Option<Duration>,
+    hard_limit: Option<Duration>,
+}
+
+impl TimeManager {
+    // No limits, the search only stops at its maximum depth.
+    pub fn infinite() -> TimeManager {
+        TimeManager {
+            start: Instant::now(),
+            soft_limit: None,
+            hard_limit: None,
+        }
+    }
+
+    pub fn new(control: &TimeControl, turn: Color) -> TimeManager {
+        let start = Instant::now();
+
+        if let Some(movetime) = control.movetime {
+            let limit = movetime
+                .saturating_sub(MOVE_OVERHEAD)
+                .max(Duration::from_millis(1));
+            return TimeManager {
+                start,
+                soft_limit: Some(limit),
+                hard_limit: Some(limit),
+            };
+        }
+
+        let (time_left, increment) = match turn {
+            Color::White => (control.wtime, control.winc),
+            Color::Black => (control.btime, control.binc),
+        };
+        let Some(time_left) = time_left else {
+            return TimeManager::infinite();
+        };
+        let increment = increment.unwrap_or_default();
+        let moves_to_go = control.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+
+        // Never plan to use more than what's left on the clock.
+        let available = time_left
+            .saturating_sub(MOVE_OVERHEAD)
+            .max(Duration::from_millis(1));
+        let budget = time_left / moves_to_go + increment * 3 / 4;
+        let soft_limit = budget.min(available);
+        // A started iteration may run past the budget, but not by so much it risks the rest of the game.
+        let hard_limit = (budget * 3).min(available / 2).max(soft_limit);
+
+        TimeManager {
+            start,
+            soft_limit: Some(soft_limit),
+            hard_limit: Some(hard_limit),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    // The next iteration usually takes several times longer than the last, so don't start one after the soft limit.
+    pub fn should_start_iteration(&self) -> bool {
+        match self.soft_limit {
+            Some(limit) => self.elapsed() < limit,
+            None => true,
+        }
+    }
+
+    pub fn out_of_time(&self) -> bool {
+        match self.hard_limit {
+            Some(limit) => self.elapsed() >= limit,
+            None => false,
+        }
+    }
+
+    pub fn soft_limit(&self) -> Option<Duration> {
+        self.soft_limit
+    }
+
+    pub fn hard_limit(&self) -> Option<Duration> {
+        self.hard_limit
+    }
+}
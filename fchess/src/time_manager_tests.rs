@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        time_manager::{TimeControl, TimeManager},
+        Color,
+    };
+
+    fn millis(milliseconds: u64) -> Option<Duration> {
+        Some(Duration::from_millis(milliseconds))
+    }
+
+    #[test]
+    fn time_manager_movetime() {
+        let control = TimeControl {
+            wtime: millis(100_000),
+            movetime: millis(1_000),
+            ..Default::default()
+        };
+        let time = TimeManager::new(&control, Color::White);
+        assert_eq!(time.soft_limit(), millis(950));
+        assert_eq!(time.hard_limit(), millis(950));
+    }
+
+    #[test]
+    fn time_manager_clock() {
+        let control = TimeControl {
+            wtime: millis(60_000),
+            btime: millis(30_000),
+            winc: millis(1_000),
+            binc: millis(0),
+            ..Default::default()
+        };
+
+        let white = TimeManager::new(&control, Color::White);
+        assert_eq!(white.soft_limit(), millis(2_750)); // 60s / 30 moves + 3/4 of the increment
+        assert_eq!(white.hard_limit(), millis(8_250));
+
+        let black = TimeManager::new(&control, Color::Black);
+        assert_eq!(black.soft_limit(), millis(1_000));
+        assert_eq!(black.hard_limit(), millis(3_000));
+
+        let control = TimeControl {
+            btime: millis(30_000),
+            movestogo: Some(2),
+            ..control
+        };
+        let black = TimeManager::new(&control, Color::Black);
+        assert_eq!(black.soft_limit(), millis(15_000));
+        assert_eq!(black.hard_limit(), millis(15_000)); // Half the clock would be less than the budget.
+    }
+
+    #[test]
+    fn time_manager_low_time() {
+        let control = TimeControl {
+            wtime: millis(40),
+            winc: millis(2_000),
+            ..Default::default()
+        };
+        let time = TimeManager::new(&control, Color::White);
+        assert!(time.soft_limit().unwrap() <= Duration::from_millis(40));
+        assert!(time.hard_limit().unwrap() <= Duration::from_millis(40));
+    }
+
+    #[test]
+    fn time_manager_infinite() {
+        let time = TimeManager::new(&TimeControl::default(), Color::White);
+        assert_eq!(time.soft_limit(), None);
+        assert!(time.should_start_iteration());
+        assert!(!time.out_of_time());
+
+        // Only black's clock was sent, so white has no limit.
+        let control = TimeControl {
+            btime: millis(1_000),
+            ..Default::default()
+        };
+        assert_eq!(TimeManager::new(&control, Color::White).hard_limit(), None);
+    }
+}
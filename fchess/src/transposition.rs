@@ -0,0 +1,147 @@
+// Transposition table, https://www.chessprogramming.org/Transposition_Table
+// Shared between search threads without locks, like PerftHashTable each slot stores the hash xored with its data.
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use crate::score::Score;
+
+pub const DEFAULT_HASH_MB: usize = 16;
+
+const BUCKET_SIZE: usize = 4; // 4 slots of 16 bytes fill a cache line.
+const AGE_MASK: u8 = 0x3F; // The age only gets 6 bits.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bound {
+    Exact, // The score is exact, every move was searched inside the window.
+    Lower, // The search failed high, the real score is at least this.
+    Upper, // The search failed low, the real score is at most this.
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TranspositionEntry {
+    pub depth: u8,
+    pub bound: Bound,
+    pub score: Score,
+    pub best_move: u16, // 0 when no move was found, e.g. all moves failed low.
+}
+
+// Layout of the data word: best move (16) | score (32) | depth (8) | bound (2) | age (6)
+// Mate scores are stored relative to the position, see Score::to_table.
+impl TranspositionEntry {
+    fn pack(&self, ply: usize, age: u8) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        (self.best_move as u64) << 48
+            | (self.score.to_table(ply) as u32 as u64) << 16
+            | (self.depth as u64) << 8
+            | bound << 6
+            | (age & AGE_MASK) as u64
+    }
+
+    fn unpack(data: u64, ply: usize) -> TranspositionEntry {
+        TranspositionEntry {
+            depth: (data >> 8) as u8,
+            bound: match (data >> 6) & 0b11 {
+                0 => Bound::Exact,
+                1 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+            score: Score::from_table((data >> 16) as u32 as i32, ply),
+            best_move: (data >> 48) as u16,
+        }
+    }
+}
+
+fn data_age(data: u64) -> u8 {
+    data as u8 & AGE_MASK
+}
+
+fn data_depth(data: u64) -> u8 {
+    (data >> 8) as u8
+}
+
+pub struct TranspositionTable {
+    buckets: Vec<[[AtomicU64; 2]; BUCKET_SIZE]>,
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> TranspositionTable {
+        let length =
+            (size_mb * 1024 * 1024 / std::mem::size_of::<[[AtomicU64; 2]; BUCKET_SIZE]>()).max(1);
+        TranspositionTable {
+            buckets: (0..length)
+                .map(|_| std::array::from_fn(|_| [AtomicU64::new(0), AtomicU64::new(0)]))
+                .collect(),
+            age: AtomicU8::new(0),
+        }
+    }
+
+    pub fn resize(&mut self, size_mb: usize) {
+        *self = TranspositionTable::new(size_mb);
+    }
+
+    pub fn clear(&mut self) {
+        for bucket in &mut self.buckets {
+            for slot in bucket {
+                *slot[0].get_mut() = 0;
+                *slot[1].get_mut() = 0;
+            }
+        }
+        *self.age.get_mut() = 0;
+    }
+
+    // Called at the start of every search, so entries from older searches get replaced first.
+    pub fn new_search(&self) {
+        self.age.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn bucket(&self, hash: u64) -> &[[AtomicU64; 2]; BUCKET_SIZE] {
+        &self.buckets[(hash % self.buckets.len() as u64) as usize]
+    }
+
+    pub fn probe(&self, hash: u64, ply: usize) -> Option<TranspositionEntry> {
+        for slot in self.bucket(hash) {
+            let check = slot[0].load(Ordering::Relaxed);
+            let data = slot[1].load(Ordering::Relaxed);
+            if check ^ data == hash && data != 0 {
+                return Some(TranspositionEntry::unpack(data, ply));
+            }
+        }
+        None
+    }
+
+    pub fn store(&self, hash: u64, ply: usize, mut entry: TranspositionEntry) {
+        let age = self.age.load(Ordering::Relaxed) & AGE_MASK;
+        let bucket = self.bucket(hash);
+
+        // Overwrite the same position if it's already here, otherwise the least valuable slot.
+        // Older entries lose value quickly since they are from a previous search.
+        let mut replace = 0;
+        let mut lowest_value = i32::MAX;
+        for (index, slot) in bucket.iter().enumerate() {
+            let check = slot[0].load(Ordering::Relaxed);
+            let data = slot[1].load(Ordering::Relaxed);
+            if check ^ data == hash {
+                if entry.best_move == 0 {
+                    entry.best_move = (data >> 48) as u16; // Keep the old move for ordering.
+                }
+                replace = index;
+                break;
+            }
+
+            let age_difference = age.wrapping_sub(data_age(data)) & AGE_MASK;
+            let value = data_depth(data) as i32 - 8 * age_difference as i32;
+            if value < lowest_value {
+                lowest_value = value;
+                replace = index;
+            }
+        }
+
+        let data = entry.pack(ply, age);
+        bucket[replace][0].store(hash ^ data, Ordering::Relaxed);
+        bucket[replace][1].store(data, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        score::Score,
+        transposition::{Bound, TranspositionEntry, TranspositionTable},
+    };
+
+    fn entry(depth: u8, score: Score) -> TranspositionEntry {
+        TranspositionEntry {
+            depth,
+            bound: Bound::Exact,
+            score,
+            best_move: 0x1234,
+        }
+    }
+
+    #[test]
+    fn transposition_store_probe() {
+        let table = TranspositionTable::new(1);
+        assert_eq!(table.probe(0xDEADBEEF, 0), None);
+
+        for (hash, bound, score) in [
+            (0xDEADBEEF, Bound::Exact, Score::DRAW),
+            (0xCAFEBABE, Bound::Lower, Score::centipawns(1234)),
+            (0x12345678, Bound::Upper, Score::centipawns(-4321)),
+        ] {
+            let stored = TranspositionEntry {
+                depth: 7,
+                bound,
+                score,
+                best_move: 0xABCD,
+            };
+            table.store(hash, 3, stored);
+            assert_eq!(table.probe(hash, 3), Some(stored));
+        }
+    }
+
+    #[test]
+    fn transposition_mate_scores() {
+        let table = TranspositionTable::new(1);
+
+        // Mated 5 plies from the root, found at ply 2, so the position is mated in 3.
+        table.store(1, 2, entry(4, Score::mated(5)));
+        // Reached again at ply 4, it's now mate 7 plies from the root.
+        assert_eq!(table.probe(1, 4).unwrap().score, Score::mated(7));
+
+        table.store(2, 6, entry(4, Score::mate(9)));
+        assert_eq!(table.probe(2, 1).unwrap().score, Score::mate(4));
+
+        // Normal scores don't depend on the ply.
+        table.store(3, 6, entry(4, Score::centipawns(250)));
+        assert_eq!(table.probe(3, 1).unwrap().score, Score::centipawns(250));
+    }
+
+    #[test]
+    fn transposition_replacement() {
+        let table = TranspositionTable::new(0); // A single bucket, so every hash collides.
+
+        for hash in 1..=4 {
+            table.store(hash, 0, entry(10 + hash as u8, Score::DRAW));
+        }
+        // The shallowest entry is replaced.
+        table.store(5, 0, entry(20, Score::DRAW));
+        assert_eq!(table.probe(1, 0), None);
+        assert!(table.probe(2, 0).is_some());
+        assert!(table.probe(5, 0).is_some());
+
+        // After a few searches the old entries are worth less than a shallow new one.
+        for _ in 0..3 {
+            table.new_search();
+        }
+        table.store(6, 0, entry(1, Score::DRAW));
+        assert_eq!(table.probe(2, 0), None);
+        assert!(table.probe(6, 0).is_some());
+
+        // Storing the same position again overwrites it, keeping the move if the new entry has none.
+        table.store(
+            6,
+            0,
+            TranspositionEntry {
+                depth: 2,
+                bound: Bound::Upper,
+                score: Score::centipawns(-50),
+                best_move: 0,
+            },
+        );
+        let probed = table.probe(6, 0).unwrap();
+        assert_eq!(probed.depth, 2);
+        assert_eq!(probed.best_move, 0x1234);
+    }
+}
@@ -0,0 +1,123 @@
+// Zobrist hashing, https://www.chessprogramming.org/Zobrist_Hashing
+// Every board feature gets a random key, the hash of a position is all of its features xored together.
+// Since xor undoes itself, move_piece can keep the hash up to date without recalculating it.
+use crate::{bitboard::BitBoard, Board, CastlingRights, Color, Pieces};
+
+pub struct ZobristKeys {
+    pub pieces: [[[u64; 64]; 6]; 2],
+    pub castling: [u64; 4], // White kingside, white queenside, black kingside, black queenside.
+    pub en_passant: [u64; 8], // By file, the rank is implied by whose turn it is.
+    pub black_to_move: u64,
+}
+
+const fn split_mix(state: u64) -> (u64, u64) {
+    // https://prng.di.unimi.it/splitmix64.c, good enough and usable in a const fn.
+    let state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    (state, z ^ (z >> 31))
+}
+
+const fn generate_keys() -> ZobristKeys {
+    let mut keys = ZobristKeys {
+        pieces: [[[0; 64]; 6]; 2],
+        castling: [0; 4],
+        en_passant: [0; 8],
+        black_to_move: 0,
+    };
+    let mut state = 123;
+    let mut key;
+
+    let mut color = 0;
+    while color < 2 {
+        let mut piece = 0;
+        while piece < 6 {
+            let mut position = 0;
+            while position < 64 {
+                (state, key) = split_mix(state);
+                keys.pieces[color][piece][position] = key;
+                position += 1;
+            }
+            piece += 1;
+        }
+        color += 1;
+    }
+
+    let mut index = 0;
+    while index < 4 {
+        (state, key) = split_mix(state);
+        keys.castling[index] = key;
+        index += 1;
+    }
+
+    let mut index = 0;
+    while index < 8 {
+        (state, key) = split_mix(state);
+        keys.en_passant[index] = key;
+        index += 1;
+    }
+
+    (_, keys.black_to_move) = split_mix(state);
+    keys
+}
+
+pub static ZOBRIST: ZobristKeys = generate_keys();
+
+pub fn castling_hash(castling_rights: CastlingRights) -> u64 {
+    let mut hash = 0;
+    if castling_rights.white_kingside {
+        hash ^= ZOBRIST.castling[0];
+    }
+    if castling_rights.white_queenside {
+        hash ^= ZOBRIST.castling[1];
+    }
+    if castling_rights.black_kingside {
+        hash ^= ZOBRIST.castling[2];
+    }
+    if castling_rights.black_queenside {
+        hash ^= ZOBRIST.castling[3];
+    }
+    hash
+}
+
+pub fn en_passant_hash(en_passant: Option<u8>) -> u64 {
+    match en_passant {
+        Some(position) => ZOBRIST.en_passant[(position % 8) as usize],
+        None => 0,
+    }
+}
+
+impl Board {
+    // From scratch, the incremental hash in move_piece should always match this.
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = castling_hash(self.castling_rights) ^ en_passant_hash(self.en_passant);
+        if self.turn == Color::Black {
+            hash ^= ZOBRIST.black_to_move;
+        }
+
+        for color in 0..2 {
+            for piece in 0..6 {
+                let mut bitboard: BitBoard = self.bitboards[color][piece];
+                while !bitboard.is_empty() {
+                    let position = bitboard.get_index_and_pop();
+                    hash ^= ZOBRIST.pieces[color][piece][position as usize];
+                }
+            }
+        }
+
+        hash
+    }
+
+    pub fn compute_pawn_hash(&self) -> u64 {
+        let mut hash = 0;
+        for color in 0..2 {
+            let mut bitboard = self.bitboards[color][Pieces::Pawn as usize];
+            while !bitboard.is_empty() {
+                let position = bitboard.get_index_and_pop();
+                hash ^= ZOBRIST.pieces[color][Pieces::Pawn as usize][position as usize];
+            }
+        }
+        hash
+    }
+}
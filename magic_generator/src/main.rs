@@ -1,313 +1,217 @@
-#![allow(clippy::needless_range_loop)]
+use std::{collections::HashSet, fs, process::exit};
 
-use std::{collections::HashSet, fs::File, io::Write};
-
-use rand::{Rng, SeedableRng};
+use rand::SeedableRng;
 use rand_chacha::ChaCha8Rng;
-
-fn vaild_position(position: [i32; 2]) -> bool {
-    (0..8).contains(&position[0]) && (0..8).contains(&position[1])
-}
-
-fn position_flatten(position: [i32; 2]) -> u8 {
-    ((position[0] % 8) + position[1] * 8) as u8
+use raycast::*;
+
+mod output;
+mod output_tests;
+mod search;
+
+use output::TableLayout;
+use search::{gen_magics, MagicTable, ShiftStrategy};
+
+const USAGE: &str = "Usage:
+    magic_generator generate [options]
+        --seed <n>              Seed for the magic search (default 123)
+        --shift <strategy>      fixed, per-square or shrink (default per-square)
+        --shrink-attempts <n>   Attempts per square before shrink gives up (default 100000)
+        --layout <layout>       shared or per-piece lookup table (default shared)
+        --format <format>       rust or binary (default rust)
+        --output <path>         Where to write the table (default ../fchess/src/magics.rs)
+    magic_generator verify [path]   Check every blocker subset against the raycaster
+    magic_generator stats [path]    Table sizes and collision info";
+
+const DEFAULT_PATH: &str = "../fchess/src/magics.rs";
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    exit(2);
 }
 
-fn calculate_sliding(direction: [i32; 2]) -> [u64; 64] {
-    let mut table = [0; 64];
-    for (position, sliding) in table.iter_mut().enumerate().take(64) {
-        let mut bitmap = 0;
-
-        let mut current_position = [position as i32 % 8, position as i32 / 8];
-
-        loop {
-            current_position[0] += direction[0];
-            current_position[1] += direction[1];
-
-            let is_vaild_position = vaild_position(current_position);
-
-            if is_vaild_position {
-                bitmap |= 1 << position_flatten(current_position);
-            } else {
-                break;
-            }
-        }
-
-        *sliding = bitmap;
-    }
-    table
-}
-
-#[derive(Clone, Copy)]
-pub struct RaycastTables {
-    pub north_west: [u64; 64],
-    pub north: [u64; 64],
-    pub north_east: [u64; 64],
-
-    pub west: [u64; 64],
-    pub east: [u64; 64],
-
-    pub south_west: [u64; 64],
-    pub south: [u64; 64],
-    pub south_east: [u64; 64],
-}
-
-impl Default for RaycastTables {
-    fn default() -> RaycastTables {
-        RaycastTables {
-            north_west: calculate_sliding([1, 1]),
-            north: calculate_sliding([0, 1]),
-            north_east: calculate_sliding([-1, 1]),
-
-            west: calculate_sliding([1, 0]),
-            east: calculate_sliding([-1, 0]),
-
-            south_west: calculate_sliding([1, -1]),
-            south: calculate_sliding([0, -1]),
-            south_east: calculate_sliding([-1, -1]),
-        }
-    }
+fn blocker_data() -> [Vec<Vec<(u64, u64)>>; 2] {
+    [
+        generate_blocker_data(generate_rook_moves_short(), generate_rook_moves()), // DO NOT LOOK AT THIS FUNCTION PLEASE
+        generate_blocker_data(generate_bishop_moves_short(), generate_bishop_moves()),
+    ]
 }
 
-const TOP: u64 = 0xff00000000000000;
-const LEFT: u64 = 0x8080808080808080;
-const RIGHT: u64 = 0x101010101010101;
-const BOTTOM: u64 = 0xff;
-
-fn generate_bishop_moves() -> [u64; 64] {
-    let tables = RaycastTables::default();
-
-    let mut north_west = tables.north_west;
-    let north_east = tables.north_east;
-    let south_west = tables.south_west;
-    let south_east = tables.south_east;
-
-    for position in 0..64 {
-        north_west[position] |= north_east[position] | south_west[position] | south_east[position];
-    }
-
-    north_west
+fn load(path: &str) -> (MagicTable, MagicTable) {
+    let data = fs::read(path).unwrap_or_else(|error| fail(&format!("{}: {}", path, error)));
+    let tables = if output::is_binary(&data) {
+        output::read_binary(&data)
+    } else {
+        output::read_rust(&String::from_utf8_lossy(&data))
+    };
+    tables.unwrap_or_else(|error| fail(&format!("{}: {}", path, error)))
 }
 
-fn generate_bishop_moves_short() -> [u64; 64] {
-    let mut rook_moves = generate_bishop_moves();
-
-    for position in 0..64 {
-        if (rook_moves[position] & TOP).count_ones() == 1 {
-            rook_moves[position] &= !TOP;
-        }
-        if (rook_moves[position] & LEFT).count_ones() == 1 {
-            rook_moves[position] &= !LEFT;
-        }
-        if (rook_moves[position] & RIGHT).count_ones() == 1 {
-            rook_moves[position] &= !RIGHT;
-        }
-        if (rook_moves[position] & BOTTOM).count_ones() == 1 {
-            rook_moves[position] &= !BOTTOM;
+fn generate(arguments: &[String]) {
+    let mut seed = 123; // Seeded random, could be optimized later.
+    let mut strategy = "per-square";
+    let mut shrink_attempts = 100_000;
+    let mut layout = TableLayout::Shared;
+    let mut binary = false;
+    let mut path = None;
+
+    let mut arguments = arguments.iter();
+    while let Some(flag) = arguments.next() {
+        let value = arguments
+            .next()
+            .unwrap_or_else(|| fail(&format!("{} needs a value", flag)));
+        match flag.as_str() {
+            "--seed" => {
+                seed = value
+                    .parse()
+                    .unwrap_or_else(|_| fail("seed must be a number"))
+            }
+            "--shift" => strategy = value,
+            "--shrink-attempts" => {
+                shrink_attempts = value
+                    .parse()
+                    .unwrap_or_else(|_| fail("shrink attempts must be a number"))
+            }
+            "--layout" => {
+                layout = match value.as_str() {
+                    "shared" => TableLayout::Shared,
+                    "per-piece" => TableLayout::PerPiece,
+                    _ => fail(&format!("unknown layout {}", value)),
+                }
+            }
+            "--format" => {
+                binary = match value.as_str() {
+                    "rust" => false,
+                    "binary" => true,
+                    _ => fail(&format!("unknown format {}", value)),
+                }
+            }
+            "--output" => path = Some(value.as_str()),
+            _ => fail(&format!("unknown option {}", flag)),
         }
     }
-
-    rook_moves
+    let strategy = match strategy {
+        "fixed" => ShiftStrategy::Fixed,
+        "per-square" => ShiftStrategy::PerSquare,
+        "shrink" => ShiftStrategy::Shrink(shrink_attempts),
+        _ => fail(&format!("unknown shift strategy {}", strategy)),
+    };
+
+    let [data_rook, data_bishop] = blocker_data();
+    let table_rook = gen_magics(&data_rook, strategy, ChaCha8Rng::seed_from_u64(seed));
+    let table_bishop = gen_magics(&data_bishop, strategy, ChaCha8Rng::seed_from_u64(seed));
+
+    print_stats(&table_rook, &table_bishop);
+
+    let path = path.unwrap_or(if binary { "magics.bin" } else { DEFAULT_PATH });
+    let contents = if binary {
+        output::write_binary(&table_rook, &table_bishop)
+    } else {
+        output::write_rust(&table_rook, &table_bishop, layout).into_bytes()
+    };
+    fs::write(path, contents).expect("creation failed"); // This should be done in a build script
+    println!("Wrote {}", path);
 }
-fn generate_rook_moves() -> [u64; 64] {
-    let tables = RaycastTables::default();
 
-    let mut north = tables.north;
-    let west = tables.west;
-    let east = tables.east;
-    let south = tables.south;
-
-    for position in 0..64 {
-        north[position] |= west[position] | east[position] | south[position];
-    }
-
-    north
-}
-
-fn generate_rook_moves_short() -> [u64; 64] {
-    let mut rook_moves = generate_rook_moves();
-
-    for position in 0..64 {
-        if (rook_moves[position] & TOP).count_ones() == 1 {
-            rook_moves[position] &= !TOP;
-        }
-        if (rook_moves[position] & LEFT).count_ones() == 1 {
-            rook_moves[position] &= !LEFT;
-        }
-        if (rook_moves[position] & RIGHT).count_ones() == 1 {
-            rook_moves[position] &= !RIGHT;
-        }
-        if (rook_moves[position] & BOTTOM).count_ones() == 1 {
-            rook_moves[position] &= !BOTTOM;
+fn verify(path: &str) -> bool {
+    let (table_rook, table_bishop) = load(path);
+    let [data_rook, data_bishop] = blocker_data();
+    let mut errors = 0;
+
+    for (name, table, data) in [
+        ("Rook", &table_rook, &data_rook),
+        ("Bishop", &table_bishop, &data_bishop),
+    ] {
+        for (position, blockers) in data.iter().enumerate() {
+            // The blocker data is made with raycast_calculate, so it's the brute force answer.
+            for &(blocker, moves) in blockers {
+                let index = table.index(position, blocker);
+                let found = table.lookup.get(index).copied();
+                if found != Some(moves) {
+                    if errors < 10 {
+                        println!(
+                            "{} on {} with blockers {:#x}: expected {:#x}, got {:?}",
+                            name, position, blocker, moves, found
+                        );
+                    }
+                    errors += 1;
+                }
+            }
         }
     }
 
-    rook_moves
-}
-
-fn raycast_calculate(position: u8, occupancy: u64, tables: &RaycastTables) -> u64 {
-    fn msb(x: u64) -> u32 {
-        63 - x.leading_zeros()
-    }
-    fn lsb(x: u64) -> u32 {
-        x.trailing_zeros()
-    }
-    let mut ray_cast_sum = 0;
-    for (ray_id, ray_table) in [
-        tables.north_west,
-        tables.north,
-        tables.north_east,
-        tables.west,
-        tables.east,
-        tables.south_west,
-        tables.south,
-        tables.south_east,
-    ]
-    .iter()
-    .enumerate()
-    {
-        let mut ray = occupancy & ray_table[position as usize];
-        if ray != 0 {
-            let r_pos = match ray_id {
-                0..4 => lsb(ray),
-                4..8 => msb(ray),
-                _ => panic!(),
-            };
-
-            ray = ray_table[r_pos as usize] ^ ray_table[position as usize];
-            ray_cast_sum |= ray;
-        } else {
-            ray_cast_sum |= ray_table[position as usize];
-        }
+    if errors == 0 {
+        println!("{}: every blocker subset matches", path);
+    } else {
+        println!("{}: {} blocker subsets don't match", path, errors);
     }
-
-    ray_cast_sum
+    errors == 0
 }
 
-pub fn generate_blocker_data(
-    slider_data: [u64; 64],
-    slider_data_full: [u64; 64],
-) -> Vec<Vec<(u64, u64)>> {
-    let mut output = Vec::new();
-    let raycast_tables = RaycastTables::default();
-    for position in 0..64 {
-        let rook_movemask = slider_data[position];
-        let mut bit_list: Vec<usize> = Vec::new();
-
-        for bit in 0..64 {
-            let bit_bitmask = 1 << bit;
-            if rook_movemask & bit_bitmask != 0 {
-                bit_list.push(bit);
-            }
-        }
-
-        let max_number_from_bits: u64 = (1 << bit_list.len()) - 1;
-
-        let mut position_all_possible_blockers = Vec::new();
-        for blocker_index in 0..=max_number_from_bits {
-            let mut blocker_pattern = 0;
-            for bit in 0..bit_list.len() {
-                let bitmask: u64 = 1 << bit;
-                let bit_location: u64 = 1 << bit_list[bit];
-                if bitmask & blocker_index != 0 {
-                    blocker_pattern |= bit_location
-                }
+fn print_stats(table_rook: &MagicTable, table_bishop: &MagicTable) {
+    let [data_rook, data_bishop] = blocker_data();
+    let mut total_entries = 0;
+    for (name, table, data) in [
+        ("Rook", table_rook, &data_rook),
+        ("Bishop", table_bishop, &data_bishop),
+    ] {
+        let mut entries = 0;
+        let mut collisions = 0;
+        let mut shrunk_squares = 0;
+        for (position, blockers) in data.iter().enumerate() {
+            let bits = table.bits(position);
+            let used: HashSet<usize> = blockers
+                .iter()
+                .map(|&(blocker, _)| table.index(position, blocker))
+                .collect();
+
+            entries += 1 << bits;
+            collisions += blockers.len() - used.len(); // Constructive, otherwise verify would fail.
+            if bits < blockers.len().trailing_zeros() {
+                shrunk_squares += 1;
             }
-
-            let sum_move = slider_data_full[position] & blocker_pattern;
-            let reduced_move = slider_data_full[position]
-                & raycast_calculate(position as u8, sum_move, &raycast_tables);
-
-            position_all_possible_blockers.push((blocker_pattern, reduced_move));
         }
-        output.push(position_all_possible_blockers)
+        let bits = (0..64).map(|position| table.bits(position));
+
+        println!(
+            "{}: {} entries ({} KiB), {}-{} bits per square, {} squares shrunk",
+            name,
+            entries,
+            entries * 8 / 1024,
+            bits.clone().min().unwrap(),
+            bits.max().unwrap(),
+            shrunk_squares
+        );
+        println!(
+            "    {} blocker patterns share an entry, {} entries are never used",
+            collisions,
+            entries - (data.iter().map(Vec::len).sum::<usize>() - collisions)
+        );
+        total_entries += entries;
     }
-
-    output
-}
-
-fn random_u64(rng: &mut ChaCha8Rng) -> u64 {
-    rng.gen()
+    println!(
+        "Total: {} entries ({} KiB)",
+        total_entries,
+        total_entries * 8 / 1024
+    );
 }
 
-const MAGIC_SHIFT_BISHOP: usize = 64 - 13; // 13 bits of magic. higher = worse
-const MAGIC_SHIFT_ROOK: usize = 64 - 12; // 12 bits of magic.
 fn main() {
-    let data_rook = generate_blocker_data(generate_rook_moves_short(), generate_rook_moves()); // DO NOT LOOK AT THIS FUNCTION PLEASE
-    let data_bishop = generate_blocker_data(generate_bishop_moves_short(), generate_bishop_moves());
-
-    let rng_rook = ChaCha8Rng::seed_from_u64(123); // Seeded random, could be optimized later.
-    let rng_bishop = ChaCha8Rng::seed_from_u64(123);
-
-    let (magic_arr_rook, magics_rook) = gen_magics(data_rook, MAGIC_SHIFT_ROOK, rng_rook);
-    let (magic_arr_bishop, magics_bishop) = gen_magics(data_bishop, MAGIC_SHIFT_BISHOP, rng_bishop);
-
-    // Meta code generation
-    let mut source_code: Vec<String> = Vec::new();
-    source_code.push("#[allow(clippy::all)]".to_string());
-    source_code.push("// Autogenerated code, do not modify here.".to_string());
-    source_code.push(format!(
-        "pub static MAGIC_SHIFT_ROOK: usize = {};",
-        MAGIC_SHIFT_ROOK
-    ));
-    source_code.push(format!(
-        "pub static MAGICS_ROOK: [u64; 64] = {:?};",
-        magic_arr_rook
-    ));
-    source_code.push(format!(
-        "pub static LOOKUP_ROOK: [[u64; {}]; 64] = {:?};",
-        magics_rook[0].len(),
-        magics_rook
-    ));
-    source_code.push(format!(
-        "pub static MAGIC_SHIFT_BISHOP: usize = {};",
-        MAGIC_SHIFT_BISHOP,
-    ));
-    source_code.push(format!(
-        "pub static MAGICS_BISHOP: [u64; 64] = {:?};",
-        magic_arr_bishop,
-    ));
-    source_code.push(format!(
-        "pub static LOOKUP_BISHOP: [[u64; {}]; 64] = {:?};",
-        magics_bishop[0].len(),
-        magics_bishop
-    ));
-
-    let mut data_file = File::create("../fchess/src/magics.rs").expect("creation failed"); // This should be done in a build script
-    data_file
-        .write_all(source_code.join("\n").as_bytes())
-        .unwrap();
-}
-
-fn gen_magics(
-    data: Vec<Vec<(u64, u64)>>,
-    shift: usize,
-    mut rng: ChaCha8Rng,
-) -> ([u64; 64], Vec<Vec<u64>>) {
-    let mut magic_arr = [0u64; 64];
-    let mut magics = Vec::new();
-
-    for (chess_position_index, result) in data.iter().enumerate() {
-        'outer: loop {
-            let mut seen_indexes = HashSet::new();
-            let max_index_lookup = 1 << ((65 - shift) - 1); // Considering N bits the max uint would be this.
-            let mut lookup_table = vec![0; max_index_lookup];
-
-            let magic = random_u64(&mut rng) & random_u64(&mut rng) & random_u64(&mut rng); // Sparsely populated magics tend to be better.
-            for position in result {
-                let index = (position.0).wrapping_mul(magic) >> shift;
-
-                if seen_indexes.contains(&index) {
-                    continue 'outer; // Retry
-                }
-                seen_indexes.insert(index);
-
-                lookup_table[index as usize] = position.1;
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    let path = arguments.get(1).map_or(DEFAULT_PATH, String::as_str);
+
+    match arguments.first().map(String::as_str) {
+        None => generate(&[]),
+        Some("generate") => generate(&arguments[1..]),
+        Some("verify") => {
+            if !verify(path) {
+                exit(1);
             }
-            magics.push(lookup_table);
-            magic_arr[chess_position_index] = magic;
-            break;
         }
+        Some("stats") => {
+            let (table_rook, table_bishop) = load(path);
+            print_stats(&table_rook, &table_bishop);
+        }
+        Some("help" | "--help" | "-h") => println!("{}", USAGE),
+        Some(command) => fail(&format!("unknown command {}", command)),
     }
-    (magic_arr, magics)
 }
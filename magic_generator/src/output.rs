@@ -0,0 +1,219 @@
+// Reading and writing magic tables, either as Rust source for fchess or as a binary blob.
+use crate::search::MagicTable;
+
+#[derive(Clone, Copy)]
+pub enum TableLayout {
+    PerPiece, // One lookup table for rooks and one for bishops.
+    Shared,   // Both pieces indexed into a single table by offset.
+}
+
+pub fn write_rust(rook: &MagicTable, bishop: &MagicTable, layout: TableLayout) -> String {
+    let mut source_code: Vec<String> = Vec::new();
+    source_code.push("#[allow(clippy::all)]".to_string());
+    source_code.push("// Autogenerated code, do not modify here.".to_string());
+    source_code.push(format!(
+        "pub static MAGICS_ROOK: [u64; 64] = {:?};",
+        rook.magics
+    ));
+    source_code.push(format!(
+        "pub static MAGIC_SHIFTS_ROOK: [u8; 64] = {:?};",
+        rook.shifts
+    ));
+    source_code.push(format!(
+        "pub static MAGICS_BISHOP: [u64; 64] = {:?};",
+        bishop.magics,
+    ));
+    source_code.push(format!(
+        "pub static MAGIC_SHIFTS_BISHOP: [u8; 64] = {:?};",
+        bishop.shifts
+    ));
+    source_code.push(format!(
+        "pub static MAGIC_OFFSETS_ROOK: [u32; 64] = {:?};",
+        rook.offsets
+    ));
+    match layout {
+        TableLayout::PerPiece => {
+            source_code.push(format!(
+                "pub static MAGIC_OFFSETS_BISHOP: [u32; 64] = {:?};",
+                bishop.offsets
+            ));
+            source_code.push(format!(
+                "pub static LOOKUP_ROOK: &[u64] = &{:?};",
+                rook.lookup
+            ));
+            source_code.push(format!(
+                "pub static LOOKUP_BISHOP: &[u64] = &{:?};",
+                bishop.lookup
+            ));
+        }
+        TableLayout::Shared => {
+            // Bishops go after the rooks, so their offsets need to be moved along.
+            let rook_length = rook.lookup.len() as u32;
+            let bishop_offsets = bishop.offsets.map(|offset| offset + rook_length);
+
+            source_code.push(format!(
+                "pub static MAGIC_OFFSETS_BISHOP: [u32; 64] = {:?};",
+                bishop_offsets
+            ));
+            source_code.push(format!(
+                "pub static LOOKUP_SLIDERS: [u64; {}] = {:?};",
+                rook.lookup.len() + bishop.lookup.len(),
+                [rook.lookup.as_slice(), bishop.lookup.as_slice()].concat()
+            ));
+            source_code.push("pub static LOOKUP_ROOK: &[u64] = &LOOKUP_SLIDERS;".to_string());
+            source_code.push("pub static LOOKUP_BISHOP: &[u64] = &LOOKUP_SLIDERS;".to_string());
+        }
+    }
+
+    source_code.join("\n")
+}
+
+// Finds `pub static NAME: TYPE = VALUE;` and parses the numbers in VALUE, following `&OTHER` references.
+// The value can be spread over many lines, the way rustfmt lays out long arrays.
+fn rust_static(source: &str, name: &str) -> Result<Vec<u64>, String> {
+    let prefix = format!("pub static {}:", name);
+    let start = source.find(&prefix).ok_or(format!("{} is missing", name))?;
+    // The type has a ; in it too, so the value starts at the = and runs to the next one.
+    let value = source[start..]
+        .split_once('=')
+        .ok_or(format!("{} has no value", name))?
+        .1;
+    let value: String = value
+        .split_once(';')
+        .ok_or(format!("{} has no closing ;", name))?
+        .0
+        .chars()
+        .filter(|character| !character.is_whitespace())
+        .collect();
+    let value = value.trim_start_matches('&');
+
+    if !value.starts_with('[') {
+        return rust_static(source, value);
+    }
+
+    let numbers = value.trim_matches(|character| character == '[' || character == ']');
+    numbers
+        .strip_suffix(',')
+        .unwrap_or(numbers)
+        .split(',')
+        .map(|number| {
+            number
+                .parse()
+                .map_err(|_| format!("{} contains {:?}, not a number", name, number))
+        })
+        .collect()
+}
+
+fn rust_table(source: &str, piece: &str) -> Result<MagicTable, String> {
+    fn squares<T: TryFrom<u64>>(values: Vec<u64>, name: &str) -> Result<[T; 64], String> {
+        let values = values
+            .into_iter()
+            .map(|value| T::try_from(value).map_err(|_| format!("{} is out of range", name)))
+            .collect::<Result<Vec<T>, String>>()?;
+        values
+            .try_into()
+            .map_err(|_| format!("{} doesn't have 64 entries", name))
+    }
+
+    let magics = format!("MAGICS_{}", piece);
+    let shifts = format!("MAGIC_SHIFTS_{}", piece);
+    let offsets = format!("MAGIC_OFFSETS_{}", piece);
+    Ok(MagicTable {
+        magics: squares(rust_static(source, &magics)?, &magics)?,
+        shifts: squares(rust_static(source, &shifts)?, &shifts)?,
+        offsets: squares(rust_static(source, &offsets)?, &offsets)?,
+        lookup: rust_static(source, &format!("LOOKUP_{}", piece))?,
+    })
+}
+
+pub fn read_rust(source: &str) -> Result<(MagicTable, MagicTable), String> {
+    Ok((rust_table(source, "ROOK")?, rust_table(source, "BISHOP")?))
+}
+
+/*
+Binary layout, all little endian:
+    "FMAG", u32 version
+    then for the rook table followed by the bishop table:
+        64 u64 magics, 64 u8 shifts, 64 u32 offsets, u32 lookup length, lookup length u64 entries
+Offsets are always relative to that piece's own lookup table.
+*/
+const BINARY_HEADER: &[u8; 4] = b"FMAG";
+const BINARY_VERSION: u32 = 1;
+
+pub fn is_binary(data: &[u8]) -> bool {
+    data.starts_with(BINARY_HEADER)
+}
+
+pub fn write_binary(rook: &MagicTable, bishop: &MagicTable) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(BINARY_HEADER);
+    data.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+    for table in [rook, bishop] {
+        for magic in table.magics {
+            data.extend_from_slice(&magic.to_le_bytes());
+        }
+        data.extend_from_slice(&table.shifts);
+        for offset in table.offsets {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data.extend_from_slice(&(table.lookup.len() as u32).to_le_bytes());
+        for entry in &table.lookup {
+            data.extend_from_slice(&entry.to_le_bytes());
+        }
+    }
+    data
+}
+
+pub fn read_binary(data: &[u8]) -> Result<(MagicTable, MagicTable), String> {
+    struct Reader<'a>(&'a [u8]);
+    impl Reader<'_> {
+        fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+            if self.0.len() < N {
+                return Err("file ends early".to_string());
+            }
+            let (bytes, rest) = self.0.split_at(N);
+            self.0 = rest;
+            Ok(bytes.try_into().unwrap())
+        }
+        fn u32(&mut self) -> Result<u32, String> {
+            Ok(u32::from_le_bytes(self.take()?))
+        }
+        fn u64(&mut self) -> Result<u64, String> {
+            Ok(u64::from_le_bytes(self.take()?))
+        }
+        fn table(&mut self) -> Result<MagicTable, String> {
+            let mut table = MagicTable {
+                magics: [0; 64],
+                shifts: [0; 64],
+                offsets: [0; 64],
+                lookup: Vec::new(),
+            };
+            for magic in table.magics.iter_mut() {
+                *magic = self.u64()?;
+            }
+            table.shifts = self.take()?;
+            for offset in table.offsets.iter_mut() {
+                *offset = self.u32()?;
+            }
+            for _ in 0..self.u32()? {
+                table.lookup.push(self.u64()?);
+            }
+            Ok(table)
+        }
+    }
+
+    let mut reader = Reader(data);
+    if &reader.take::<4>()? != BINARY_HEADER {
+        return Err("not a magic table blob".to_string());
+    }
+    let version = reader.u32()?;
+    if version != BINARY_VERSION {
+        return Err(format!("unsupported blob version {}", version));
+    }
+    let rook = reader.table()?;
+    let bishop = reader.table()?;
+    if !reader.0.is_empty() {
+        return Err("trailing data after the bishop table".to_string());
+    }
+    Ok((rook, bishop))
+}
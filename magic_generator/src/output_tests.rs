@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, process::Command};
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use crate::{
+        blocker_data,
+        output::{read_rust, write_rust, TableLayout},
+        search::{gen_magics, ShiftStrategy},
+        verify,
+    };
+
+    #[test]
+    fn rustfmt_table_verifies() {
+        let [data_rook, data_bishop] = blocker_data();
+        let rook = gen_magics(
+            &data_rook,
+            ShiftStrategy::PerSquare,
+            ChaCha8Rng::seed_from_u64(1),
+        );
+        let bishop = gen_magics(
+            &data_bishop,
+            ShiftStrategy::PerSquare,
+            ChaCha8Rng::seed_from_u64(1),
+        );
+
+        for (layout, name) in [
+            (TableLayout::Shared, "shared"),
+            (TableLayout::PerPiece, "per_piece"),
+        ] {
+            let path = std::env::temp_dir().join(format!("magic_generator_{}.rs", name));
+            fs::write(&path, write_rust(&rook, &bishop, layout)).unwrap();
+            let status = Command::new("rustfmt")
+                .args(["--edition", "2021"])
+                .arg(&path)
+                .status()
+                .expect("rustfmt should be installed");
+            assert!(status.success());
+
+            // Every array is spread over many lines now, and ends with a trailing comma.
+            let formatted = fs::read_to_string(&path).unwrap();
+            assert!(formatted.contains("pub static MAGICS_ROOK: [u64; 64] = [\n"));
+            let (rook_read, bishop_read) = read_rust(&formatted).unwrap();
+            assert_eq!(rook_read.magics, rook.magics);
+            assert_eq!(bishop_read.offsets.len(), 64);
+            assert!(verify(path.to_str().unwrap()));
+            fs::remove_file(&path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+
+fn random_u64(rng: &mut ChaCha8Rng) -> u64 {
+    rng.gen()
+}
+
+#[derive(Clone, Copy)]
+pub enum ShiftStrategy {
+    Fixed, // Every square gets as many index bits as the worst square, wastes a lot of space on squares with few relevant bits.
+    PerSquare, // Index bits equal to the number of relevant blocker bits on that square.
+    Shrink(usize), // PerSquare, then keep trying to drop a bit, giving up on a square after this many attempts.
+}
+
+pub struct MagicTable {
+    pub magics: [u64; 64],
+    pub shifts: [u8; 64],
+    pub offsets: [u32; 64],
+    pub lookup: Vec<u64>,
+}
+
+impl MagicTable {
+    pub fn index(&self, position: usize, blockers: u64) -> usize {
+        self.offsets[position] as usize
+            + (blockers.wrapping_mul(self.magics[position]) >> self.shifts[position]) as usize
+    }
+
+    pub fn bits(&self, position: usize) -> u32 {
+        64 - self.shifts[position] as u32
+    }
+}
+
+fn find_magic(
+    blockers: &[(u64, u64)],
+    bits: u32,
+    rng: &mut ChaCha8Rng,
+    max_attempts: Option<usize>,
+) -> Option<(u64, Vec<u64>)> {
+    let shift = 64 - bits;
+    let mut lookup_table = vec![0; 1 << bits];
+    let mut seen_at = vec![0usize; 1 << bits]; // Attempt number the entry was last written in, saves clearing the table.
+
+    let mut attempt = 0;
+    'outer: while max_attempts.is_none_or(|max| attempt < max) {
+        attempt += 1;
+
+        let magic = random_u64(rng) & random_u64(rng) & random_u64(rng); // Sparsely populated magics tend to be better.
+        for &(blocker, moves) in blockers {
+            let index = (blocker.wrapping_mul(magic) >> shift) as usize;
+
+            if seen_at[index] == attempt {
+                if lookup_table[index] != moves {
+                    continue 'outer; // Retry
+                }
+            } else {
+                seen_at[index] = attempt;
+                lookup_table[index] = moves;
+            }
+        }
+
+        for index in 0..lookup_table.len() {
+            if seen_at[index] != attempt {
+                lookup_table[index] = 0; // Leftovers from failed attempts.
+            }
+        }
+        return Some((magic, lookup_table));
+    }
+
+    None
+}
+
+pub fn gen_magics(
+    data: &[Vec<(u64, u64)>],
+    strategy: ShiftStrategy,
+    mut rng: ChaCha8Rng,
+) -> MagicTable {
+    let mut table = MagicTable {
+        magics: [0; 64],
+        shifts: [0; 64],
+        offsets: [0; 64],
+        lookup: Vec::new(),
+    };
+
+    // Every subset of the mask is present, so the amount of blocker patterns is always a power of two.
+    let max_relevant_bits = data
+        .iter()
+        .map(|blockers| blockers.len().trailing_zeros())
+        .max()
+        .unwrap();
+
+    for (chess_position_index, blockers) in data.iter().enumerate() {
+        let bits = match strategy {
+            ShiftStrategy::Fixed => max_relevant_bits,
+            ShiftStrategy::PerSquare | ShiftStrategy::Shrink(_) => blockers.len().trailing_zeros(),
+        };
+
+        let (mut magic, mut lookup_table) =
+            find_magic(blockers, bits, &mut rng, None).expect("unbounded search always succeeds");
+        let mut bits = bits;
+
+        if let ShiftStrategy::Shrink(max_attempts) = strategy {
+            while let Some((smaller_magic, smaller_table)) =
+                find_magic(blockers, bits - 1, &mut rng, Some(max_attempts))
+            {
+                magic = smaller_magic;
+                lookup_table = smaller_table;
+                bits -= 1;
+            }
+        }
+
+        table.magics[chess_position_index] = magic;
+        table.shifts[chess_position_index] = (64 - bits) as u8;
+        table.offsets[chess_position_index] = table.lookup.len() as u32;
+        table.lookup.append(&mut lookup_table);
+    }
+    table
+}
@@ -0,0 +1,156 @@
+// Runs every position in a perftsuite.epd style file, each line looks like:
+// rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 ;D1 20 ;D2 400 ;D3 8902
+use std::{fs, process::exit, str::FromStr, time::Instant};
+
+use fchess::{
+    chess_data::fen_parser,
+    move_generation::{perft_hashed, perft_parallel, PerftHashTable},
+    structs::ChessTables,
+};
+
+const USAGE: &str =
+    "Usage: perft_suite <perftsuite.epd> [--depth <max depth>] [--threads <n>] [--hash <MB, 0 for none>]";
+
+struct SuiteEntry {
+    fen: String,
+    expected: Vec<(u8, usize)>, // (depth, node count)
+}
+
+fn parse_line(line: &str) -> Result<SuiteEntry, String> {
+    let mut fields = line.split(';');
+    let fen = fields.next().unwrap().trim().to_string();
+
+    let mut expected = Vec::new();
+    for field in fields {
+        let (depth, nodes) = field
+            .trim()
+            .split_once(' ')
+            .ok_or(format!("{:?} isn't a depth and node count", field))?;
+        let depth = depth
+            .strip_prefix('D')
+            .and_then(|depth| depth.parse().ok())
+            .ok_or(format!("{:?} isn't a depth", depth))?;
+        let nodes = nodes
+            .trim()
+            .parse()
+            .map_err(|_| format!("{:?} isn't a node count", nodes))?;
+        expected.push((depth, nodes));
+    }
+
+    Ok(SuiteEntry { fen, expected })
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}\n{}", message, USAGE);
+    exit(2);
+}
+
+// Parsed straight into the type it's stored as, so a value that doesn't fit is an error instead of wrapping.
+fn number<T: FromStr>(argument: &str, value: Option<&String>, range: &str) -> T {
+    value
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| fail(&format!("{} needs a number{}", argument, range)))
+}
+
+fn main() {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    let mut path = None;
+    let mut max_depth = u8::MAX;
+    let mut threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let mut hash_size_mb = 64;
+
+    let mut arguments = arguments.iter();
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--depth" => max_depth = number(argument, arguments.next(), " from 0 to 255"),
+            "--threads" => threads = number(argument, arguments.next(), ""),
+            "--hash" => hash_size_mb = number(argument, arguments.next(), ""),
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if path.is_none() => path = Some(argument.clone()),
+            _ => fail(&format!("unexpected argument {}", argument)),
+        }
+    }
+    let path = path.unwrap_or_else(|| fail("no suite file given"));
+
+    let suite =
+        fs::read_to_string(&path).unwrap_or_else(|error| fail(&format!("{}: {}", path, error)));
+    let tables = ChessTables::default();
+    // --hash 0 turns the table off rather than leaving a single entry that every position fights over.
+    let hash_table = (hash_size_mb > 0).then(|| PerftHashTable::new(hash_size_mb));
+
+    let start = Instant::now();
+    let mut total_nodes = 0;
+    let mut passed = 0;
+    let mut failed = 0;
+
+    for (line_number, line) in suite.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let entry = match parse_line(line) {
+            Ok(entry) => entry,
+            Err(error) => {
+                println!("line {}: {}", line_number + 1, error);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let board = fen_parser(&entry.fen);
+        let mut position_passed = true;
+        for &(depth, expected_nodes) in entry
+            .expected
+            .iter()
+            .filter(|(depth, _)| *depth <= max_depth)
+        {
+            let result = match &hash_table {
+                Some(hash_table) => {
+                    perft_hashed(board.clone(), depth, &tables, threads, hash_table)
+                }
+                None => perft_parallel(board.clone(), depth, &tables, threads),
+            };
+            total_nodes += result.nodes;
+            if result.nodes == expected_nodes {
+                continue;
+            }
+
+            // Deeper depths will be wrong as well, the divide at the first bad depth is the useful part.
+            println!(
+                "FAIL line {}: {} depth {}: expected {}, got {}",
+                line_number + 1,
+                entry.fen,
+                depth,
+                expected_nodes,
+                result.nodes
+            );
+            for (chess_move, nodes) in &result.divide {
+                println!("    {}: {}", chess_move, nodes);
+            }
+            position_passed = false;
+            break;
+        }
+
+        if position_passed {
+            println!("ok   line {}: {}", line_number + 1, entry.fen);
+            passed += 1;
+        } else {
+            failed += 1;
+        }
+    }
+
+    let elapsed = start.elapsed();
+    println!(
+        "{} passed, {} failed, {} nodes in {:.2}s ({:.0} nodes/s)",
+        passed,
+        failed,
+        total_nodes,
+        elapsed.as_secs_f64(),
+        total_nodes as f64 / elapsed.as_secs_f64()
+    );
+    if failed != 0 {
+        exit(1);
+    }
+}
@@ -1,9 +1,13 @@
-use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
 use std::{io::Write, net::TcpStream};
 
-use fchess::engine::get_best_move;
+use fchess::engine::{bench, search, SearchLimits, SearchResult, BENCH_DEPTH};
 use fchess::move_generation::*;
 use fchess::structs::{Board, ChessMove, ChessTables};
+use fchess::transposition::{TranspositionTable, DEFAULT_HASH_MB};
 use text_io::read;
 
 const OUTPUT_ADDR: &str = "127.0.0.1:2024";
@@ -25,6 +29,13 @@ impl Uci {
         }
         text
     }
+    // The search thread gets its own copy to print info and bestmove.
+    fn try_clone(&self) -> Uci {
+        Uci(self
+            .0
+            .as_ref()
+            .map(|stream| stream.try_clone().expect("Failed to clone output stream")))
+    }
     fn debug(&mut self, text: &str) {
         // Send message to debug handler, not sent to UCI.
         if let Some(stream) = &mut self.0 {
@@ -57,6 +68,95 @@ fn parse_command(command: &str) -> (u8, u8) {
     (position_first_int as u8, position_second_int as u8)
 }
 
+// Prints the node count signature, compare it before and after a change to check the search is unaffected.
+fn run_bench(uci: &mut Uci, depth: Option<&str>, tables: &ChessTables) {
+    let depth = match depth {
+        Some(depth) => depth.parse().expect("depth provided wasn't a vaild usize"),
+        None => BENCH_DEPTH,
+    };
+    let results = bench(depth, tables);
+    let milliseconds = results.elapsed.as_millis().max(1);
+
+    uci.put(&format!("Total time (ms): {}", milliseconds));
+    uci.put(&format!("Nodes searched: {}", results.nodes));
+    uci.put(&format!(
+        "Nodes/second: {}",
+        results.nodes as u128 * 1000 / milliseconds
+    ));
+}
+
+// go [wtime <x>] [btime <x>] [winc <x>] [binc <x>] [movestogo <x>] [movetime <x>]
+//    [depth <x>] [nodes <x>] [mate <x>] [infinite] [searchmoves <move1> ... <movei>]
+fn parse_go(arguments: &[&str], board: &Board, tables: &ChessTables) -> SearchLimits {
+    let mut limits = SearchLimits::default();
+    let time_control = &mut limits.time_control;
+
+    let mut index = 0;
+    while index < arguments.len() {
+        let value = arguments.get(index + 1).copied().unwrap_or("");
+        let milliseconds = || {
+            // Some GUIs send a negative time when the clock has run out.
+            let value: i64 = value.parse().expect("time provided wasn't a vaild integer");
+            Some(Duration::from_millis(value.max(0) as u64))
+        };
+        match arguments[index] {
+            "wtime" => time_control.wtime = milliseconds(),
+            "btime" => time_control.btime = milliseconds(),
+            "winc" => time_control.winc = milliseconds(),
+            "binc" => time_control.binc = milliseconds(),
+            "movetime" => time_control.movetime = milliseconds(),
+            "movestogo" => {
+                time_control.movestogo = Some(
+                    value
+                        .parse()
+                        .expect("movestogo provided wasn't a vaild u32"),
+                )
+            }
+            "depth" => {
+                limits.depth = Some(value.parse().expect("depth provided wasn't a vaild usize"))
+            }
+            "nodes" => {
+                limits.nodes = Some(value.parse().expect("nodes provided wasn't a vaild u64"))
+            }
+            "mate" => limits.mate = Some(value.parse().expect("mate provided wasn't a vaild u32")),
+            "infinite" => limits.infinite = true,
+            "searchmoves" => {
+                // The moves run until the next keyword, anything that isn't a legal move ends the list.
+                let legal_moves = board.get_all_legal_moves(tables);
+                let legal_moves = &legal_moves.move_buffer[..legal_moves.length as usize];
+                while let Some(&chess_move) = legal_moves.iter().find(|&&chess_move| {
+                    arguments.get(index + 1)
+                        == Some(&ChessMove::unpack(chess_move).to_uci().as_str())
+                }) {
+                    limits.searchmoves.push(chess_move);
+                    index += 1;
+                }
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+
+    limits
+}
+
+fn info_line(result: &SearchResult) -> String {
+    let milliseconds = result.elapsed.as_millis();
+    let pv: Vec<String> = result.pv.iter().map(ChessMove::to_uci).collect();
+    format!(
+        "info depth {} seldepth {} score {} nodes {} nps {} time {} pv {}",
+        result.depth,
+        result.seldepth,
+        result.score,
+        result.nodes,
+        result.nodes as u128 * 1000 / milliseconds.max(1),
+        milliseconds,
+        pv.join(" ")
+    )
+}
+
+const MAX_THREADS: usize = 256;
+
 static DEBUGGING: bool = false;
 fn main() {
     let mut uci = if DEBUGGING {
@@ -69,56 +169,137 @@ fn main() {
 
     uci.debug("START");
 
-    let tables = ChessTables::default();
+    let tables = Arc::new(ChessTables::default());
+
+    // `uci bench [depth]` runs the bench and exits, so it can be scripted without a GUI.
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("bench") {
+        run_bench(&mut uci, args.get(2).map(String::as_str), &tables);
+        return;
+    }
 
     let mut board = Board::default();
-    let mut board_history = HashMap::new();
+    let mut board_history = Vec::new(); // Hashes of the positions before the current one, since the last irreversible move.
+    let mut transposition_table = Arc::new(TranspositionTable::new(DEFAULT_HASH_MB));
+    let mut threads = 1;
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut search_thread: Option<JoinHandle<()>> = None;
 
     loop {
         let command = uci.get();
         let command_split = command.split(" ").collect::<Vec<&str>>();
+
+        // The search runs on its own thread so "stop" can be read while it's going.
+        // Anything that changes the engine's state has to wait for it to finish first.
+        // "isready", "ponderhit" and unknown commands don't, so they're answered straight away.
+        if matches!(
+            command_split[0],
+            "uci" | "quit" | "stop" | "ucinewgame" | "setoption" | "bench" | "go" | "position"
+        ) {
+            if command_split[0] == "stop" || command_split[0] == "quit" {
+                stop.store(true, Ordering::Relaxed);
+            }
+            if let Some(handle) = search_thread.take() {
+                handle.join().expect("search thread panicked");
+            }
+        }
+
         match command_split[0] {
             "uci" => {
                 uci.put("id name Fyacp");
                 uci.put("id author Zander");
+                uci.put(&format!(
+                    "option name Hash type spin default {} min 1 max 65536",
+                    DEFAULT_HASH_MB
+                ));
+                uci.put(&format!(
+                    "option name Threads type spin default 1 min 1 max {}",
+                    MAX_THREADS
+                ));
                 uci.put("uciok");
             }
             "isready" => uci.put("readyok"),
             "quit" => break,
-            "ucinewgame" => {}
+            "stop" => {} // Already handled above.
+            "ucinewgame" => Arc::get_mut(&mut transposition_table)
+                .expect("the search has finished")
+                .clear(),
+            "setoption" => {
+                // setoption name <id> value <x>
+                let name_index = command_split.iter().position(|&r| r == "name");
+                let value_index = command_split.iter().position(|&r| r == "value");
+                if let (Some(name_index), Some(value_index)) = (name_index, value_index) {
+                    let name = command_split[name_index + 1..value_index].join(" ");
+                    let value = command_split[value_index + 1..].join(" ");
+                    if name.eq_ignore_ascii_case("hash") {
+                        match value.parse::<usize>() {
+                            Ok(size_mb) => Arc::get_mut(&mut transposition_table)
+                                .expect("the search has finished")
+                                .resize(size_mb.max(1)),
+                            Err(_) => uci.debug(&format!("Invalid hash size {}", value)),
+                        }
+                    } else if name.eq_ignore_ascii_case("threads") {
+                        match value.parse::<usize>() {
+                            Ok(count) => threads = count.clamp(1, MAX_THREADS),
+                            Err(_) => uci.debug(&format!("Invalid thread count {}", value)),
+                        }
+                    }
+                }
+            }
+            "bench" => run_bench(&mut uci, command_split.get(1).copied(), &tables),
 
-            "go" => match command_split[1] {
-                "perft" => {
+            "go" => match command_split.get(1).copied() {
+                Some("perft") => {
                     let depth: u8 = command_split[2]
                         .parse()
                         .expect("depth provided wasn't a vaild usize");
-                    let results = perft(board.clone(), depth, &tables);
+                    let threads =
+                        std::thread::available_parallelism().map_or(1, |threads| threads.get());
+                    let results = perft_parallel(board.clone(), depth, &tables, threads);
 
-                    let result_string = format!("\n{}", results);
-                    uci.put(&result_string);
+                    for (chess_move, nodes) in &results.divide {
+                        uci.put(&format!("{}: {}", chess_move, nodes));
+                    }
+                    uci.put("");
+                    uci.put(&format!("Nodes searched: {}", results.nodes));
                 }
-                "wtime" => {
-                    let chess_move = ChessMove::unpack(get_best_move(
-                        5,
-                        board.clone(),
-                        board_history.clone(),
-                        &tables,
-                    ));
-                    let suffix = match chess_move.move_type {
-                        fchess::structs::MoveType::QueenPromotion => "q",
-                        fchess::structs::MoveType::RookPromotion => "r",
-                        fchess::structs::MoveType::BishopPromotion => "b",
-                        fchess::structs::MoveType::KnightPromotion => "k",
-                        _ => "",
+                _ => {
+                    let limits = SearchLimits {
+                        threads,
+                        ..parse_go(&command_split[1..], &board, &tables)
                     };
-                    uci.put(&format!(
-                        "bestmove {}{}{}",
-                        human_readable_position(chess_move.origin).to_lowercase(),
-                        human_readable_position(chess_move.destination).to_lowercase(),
-                        suffix
-                    ));
+                    stop.store(false, Ordering::Relaxed);
+
+                    let mut uci = uci.try_clone();
+                    let board = board.clone();
+                    let board_history = board_history.clone();
+                    let tables = Arc::clone(&tables);
+                    let transposition_table = Arc::clone(&transposition_table);
+                    let stop = Arc::clone(&stop);
+                    search_thread = Some(thread::spawn(move || {
+                        let result = search(
+                            &board,
+                            &board_history,
+                            &tables,
+                            &transposition_table,
+                            &limits,
+                            &stop,
+                            |result| uci.put(&info_line(result)),
+                        );
+                        // "0000" is the null move, sent when there are no legal moves.
+                        let best_move = result
+                            .best_move
+                            .map_or("0000".to_string(), |best_move| best_move.to_uci());
+                        match result.ponder_move {
+                            Some(ponder_move) => uci.put(&format!(
+                                "bestmove {} ponder {}",
+                                best_move,
+                                ponder_move.to_uci()
+                            )),
+                            None => uci.put(&format!("bestmove {}", best_move)),
+                        }
+                    }));
                 }
-                _ => {}
             },
             "position" => {
                 let moves_index = command_split.iter().position(|&r| r == "moves");
@@ -137,34 +318,34 @@ fn main() {
                     _ => panic!(),
                 };
 
-                board_history = HashMap::new();
-                board_history.insert(board.bitboards, 1);
+                board_history.clear();
                 if let Some(index) = moves_index {
                     for chess_move in &command_split[index + 1..] {
-                        let possible_seen_count = board_history.get(&board.bitboards);
-                        match possible_seen_count {
-                            Some(value) => board_history.insert(board.bitboards, value + 1),
-                            None => board_history.insert(board.bitboards, 1),
-                        };
+                        let previous_board = board.clone();
                         let move_data = parse_command(chess_move);
 
                         let mut promotion_preference = 'q';
                         if chess_move.len() == 5 {
                             promotion_preference = chess_move.chars().nth(4).unwrap();
                         }
-                        board.try_make_move(
+                        let played = board.try_make_move(
                             move_data.0,
                             move_data.1,
                             promotion_preference,
                             &tables,
                         );
+                        // Captures and pawn moves, none of the positions before one can come up again.
+                        match played.map(|played| previous_board.is_irreversible(played)) {
+                            Some(true) => board_history.clear(),
+                            _ => board_history.push(previous_board.hash),
+                        }
                     }
                 }
             }
 
-            _ => {
-                break;
-            }
+            // Pondering isn't supported, "go ponder" already searches on the normal clock.
+            "ponderhit" => {}
+            _ => {} // The protocol says to ignore anything unknown.
         }
     }
 